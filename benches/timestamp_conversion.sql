@@ -0,0 +1,56 @@
+-- Throughput of the conversions between UUIDs and timestamptz.
+-- Run with `make bench`, or in psql against a database with pgx_uuidv7 installed.
+-- Each query is run several times; compare the best timings.
+
+\set ON_ERROR_STOP on
+\pset pager off
+\timing on
+
+CREATE EXTENSION IF NOT EXISTS pgx_uuidv7;
+
+CREATE TEMP TABLE bench_uuid AS
+SELECT uuid_generate_v7('2012-03-04T05:06:07+00:00'::timestamptz + make_interval(secs => n * 0.001)) AS v7,
+       uuid_generate_v1('2012-03-04T05:06:07+00:00'::timestamptz + make_interval(secs => n * 0.001)) AS v1,
+       '2012-03-04T05:06:07+00:00'::timestamptz + make_interval(secs => n * 0.001) AS ts
+FROM generate_series(1, 1000000) AS n;
+VACUUM ANALYZE bench_uuid;
+
+\echo 'baseline: scan only'
+SELECT count(v7) FROM bench_uuid;
+SELECT count(v7) FROM bench_uuid;
+SELECT count(v7) FROM bench_uuid;
+
+\echo 'native uuid_extract_timestamp (v7)'
+SELECT max(uuid_extract_timestamp(v7)) FROM bench_uuid;
+SELECT max(uuid_extract_timestamp(v7)) FROM bench_uuid;
+SELECT max(uuid_extract_timestamp(v7)) FROM bench_uuid;
+
+\echo 'uuid_to_timestamptz (v7)'
+SELECT max(uuid_to_timestamptz(v7)) FROM bench_uuid;
+SELECT max(uuid_to_timestamptz(v7)) FROM bench_uuid;
+SELECT max(uuid_to_timestamptz(v7)) FROM bench_uuid;
+
+\echo 'uuid_to_timestamptz_precise (v7)'
+SELECT max(uuid_to_timestamptz_precise(v7)) FROM bench_uuid;
+SELECT max(uuid_to_timestamptz_precise(v7)) FROM bench_uuid;
+SELECT max(uuid_to_timestamptz_precise(v7)) FROM bench_uuid;
+
+\echo 'native uuid_extract_timestamp (v1)'
+SELECT max(uuid_extract_timestamp(v1)) FROM bench_uuid;
+SELECT max(uuid_extract_timestamp(v1)) FROM bench_uuid;
+SELECT max(uuid_extract_timestamp(v1)) FROM bench_uuid;
+
+\echo 'uuid_to_timestamptz (v1)'
+SELECT max(uuid_to_timestamptz(v1)) FROM bench_uuid;
+SELECT max(uuid_to_timestamptz(v1)) FROM bench_uuid;
+SELECT max(uuid_to_timestamptz(v1)) FROM bench_uuid;
+
+\echo 'timestamptz_to_uuid_v7_min'
+SELECT max(timestamptz_to_uuid_v7_min(ts)) FROM bench_uuid;
+SELECT max(timestamptz_to_uuid_v7_min(ts)) FROM bench_uuid;
+SELECT max(timestamptz_to_uuid_v7_min(ts)) FROM bench_uuid;
+
+\echo 'uuid_generate_v7(timestamptz)'
+SELECT count(uuid_generate_v7(ts)) FROM bench_uuid;
+SELECT count(uuid_generate_v7(ts)) FROM bench_uuid;
+SELECT count(uuid_generate_v7(ts)) FROM bench_uuid;
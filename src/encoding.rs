@@ -0,0 +1,175 @@
+use crate::my_converter::Converter;
+use crate::ulid;
+use pgrx::prelude::*;
+
+/// Bitcoin's Base58 alphabet, without `0`, `O`, `I` and `l`.
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Number of digits of 128 bits in Base58 and Base62, and in unpadded Base64.
+const SHORT_LEN: usize = 22;
+
+/// The text encodings of a UUID supported by `uuid_encode` and `uuid_decode`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Format {
+    Base58,
+    Base62,
+    Base64Url,
+    Hex,
+    Braced,
+    Ulid,
+}
+
+impl Format {
+    fn parse(format: &str) -> Format {
+        let f = match format.to_ascii_lowercase().as_str() {
+            "base58" => Some(Format::Base58),
+            "base62" => Some(Format::Base62),
+            "base64url" => Some(Format::Base64Url),
+            "hex" => Some(Format::Hex),
+            "braced" => Some(Format::Braced),
+            "ulid" => Some(Format::Ulid),
+            _ => None,
+        };
+        if f.is_none() {
+            ereport!(
+                ERROR,
+                PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+                format!("unrecognized UUID encoding \"{format}\""),
+                "Valid encodings are \"base58\", \"base62\", \"base64url\", \"hex\", \"braced\" and \"ulid\"."
+            );
+        }
+        f.unwrap()
+    }
+
+    fn encode(self, v: u128) -> String {
+        match self {
+            Format::Base58 => encode_base(v, BASE58_ALPHABET),
+            Format::Base62 => encode_base(v, BASE62_ALPHABET),
+            Format::Base64Url => encode_base64url(v),
+            Format::Hex => format!("{v:032x}"),
+            Format::Braced => uuid::Uuid::from_u128(v).braced().to_string(),
+            Format::Ulid => ulid::encode(v),
+        }
+    }
+
+    fn decode(self, s: &str) -> Option<u128> {
+        match self {
+            Format::Base58 => decode_base(s, BASE58_ALPHABET),
+            Format::Base62 => decode_base(s, BASE62_ALPHABET),
+            Format::Base64Url => decode_base64url(s),
+            Format::Hex if s.len() == 32 => uuid::Uuid::try_parse(s).ok().map(|u| u.as_u128()),
+            Format::Braced if s.len() == 38 => uuid::Uuid::try_parse(s).ok().map(|u| u.as_u128()),
+            Format::Hex | Format::Braced => None,
+            Format::Ulid => ulid::decode(s),
+        }
+    }
+}
+
+/// Encode 128 bits as a number in the base of the alphabet,
+/// left-padded with its zero digit to `SHORT_LEN` digits so that the text sorts like the UUID.
+fn encode_base(mut v: u128, alphabet: &[u8]) -> String {
+    let base = alphabet.len() as u128;
+    let mut digits = [alphabet[0]; SHORT_LEN];
+    for d in digits.iter_mut().rev() {
+        *d = alphabet[(v % base) as usize];
+        v /= base;
+    }
+    digits.iter().map(|&d| d as char).collect()
+}
+
+/// Decode a number in the base of the alphabet, of at most `SHORT_LEN` digits.
+/// Shorter text is taken as left-padded, so unpadded encoders are also accepted.
+fn decode_base(s: &str, alphabet: &[u8]) -> Option<u128> {
+    if s.is_empty() || s.len() > SHORT_LEN {
+        return None;
+    }
+    let base = alphabet.len() as u128;
+    s.bytes().try_fold(0u128, |acc, c| {
+        let d = alphabet.iter().position(|&a| a == c)? as u128;
+        acc.checked_mul(base)?.checked_add(d)
+    })
+}
+
+/// Encode the 16 bytes in URL-safe Base64 without padding, 6 bits per digit.
+/// The last digit holds the 2 remaining bits, followed by 4 zero bits.
+fn encode_base64url(v: u128) -> String {
+    (0..SHORT_LEN)
+        .map(|i| {
+            let shift = 128 - 6 * (i as i32 + 1);
+            let bits = if shift >= 0 { v >> shift } else { v << -shift };
+            BASE64URL_ALPHABET[(bits & 0x3f) as usize] as char
+        })
+        .collect()
+}
+
+/// Decode URL-safe Base64 of 16 bytes, with or without the `==` padding.
+/// The unused low bits of the last digit must be zero.
+fn decode_base64url(s: &str) -> Option<u128> {
+    let s = s.strip_suffix("==").unwrap_or(s);
+    if s.len() != SHORT_LEN {
+        return None;
+    }
+    let digit = |c: u8| {
+        BASE64URL_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .map(|d| d as u128)
+    };
+    let (head, last) = s.as_bytes().split_at(SHORT_LEN - 1);
+    let v = head
+        .iter()
+        .try_fold(0u128, |acc, &c| Some(acc << 6 | digit(c)?))?;
+    let last = digit(last[0])?;
+    (last & 0x0f == 0).then_some(v << 2 | last >> 4)
+}
+
+/// Encode a UUID as text in the given format:
+/// `base58`, `base62`, `base64url`, `hex` (32 digits without dashes), `braced` or `ulid`.
+/// Base58 and Base62 are left-padded to 22 digits, so they sort like the UUID.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_encode(uuid: pgrx::Uuid, format: &str) -> String {
+    let u: uuid::Uuid = Converter(uuid).into();
+    Format::parse(format).encode(u.as_u128())
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_encode"(uuid, text)
+IS 'Encode a UUID as text in the given format: base58, base62, base64url, hex (32 digits without dashes), braced or ulid. Base58 and Base62 are left-padded to 22 digits, so they sort like the UUID.';
+"#,
+    name = "comment_uuid_encode",
+    requires = [uuid_encode],
+);
+
+/// Decode a UUID from text in the given format, as produced by `uuid_encode`.
+/// Base58 and Base62 of fewer than 22 digits are also accepted,
+/// and so is URL-safe Base64 with the `==` padding.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_decode(text: &str, format: &str) -> pgrx::Uuid {
+    let f = Format::parse(format);
+    let v = f.decode(text);
+    if v.is_none() {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION,
+            format!(
+                "invalid input syntax for UUID in {}: \"{text}\"",
+                format.to_ascii_lowercase()
+            )
+        );
+    }
+    Converter(uuid::Uuid::from_u128(v.unwrap())).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_decode"(text, text)
+IS 'Decode a UUID from text in the given format, as produced by uuid_encode. Base58 and Base62 of fewer than 22 digits are also accepted, and so is URL-safe Base64 with the == padding.';
+"#,
+    name = "comment_uuid_decode",
+    requires = [uuid_decode],
+);
@@ -0,0 +1,311 @@
+use pgrx::prelude::*;
+use pgrx::{pg_shmem_init, PgAtomic};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of bits of sub-millisecond precision stored in `rand_a` (RFC 9562 method 3).
+pub(crate) const SUBMS_BITS: u32 = 12;
+
+/// Number of bits of the fixed-length counter (RFC 9562 method 1),
+/// stored in `rand_a` and the top 30 bits of `rand_b`.
+pub(crate) const COUNTER_BITS: u32 = 42;
+pub(crate) const COUNTER_MAX: u64 = (1 << COUNTER_BITS) - 1;
+// The most significant bit is left unset when seeding,
+// so the counter can take at least 2^41 increments before it overflows.
+const COUNTER_SEED_MASK: u64 = COUNTER_MAX >> 1;
+
+thread_local! {
+    // Last timestamp issued by this backend, in the format of `now_with_submillis`.
+    static LAST_TIMESTAMP: Cell<u64> = const { Cell::new(0) };
+
+    // Counter used by this backend for UUIDs with a given timestamp.
+    static COUNTER: CounterV7 = const { CounterV7::new() };
+
+    // Clock sequence state of this backend for UUIDv1.
+    static CLOCK_SEQ: ClockSeq = const { ClockSeq::new() };
+
+    // Random node ID of this backend for UUIDv1, generated on first use.
+    static NODE_ID: Cell<Option<[u8; 6]>> = const { Cell::new(None) };
+}
+
+// Last timestamp issued by any backend of the cluster, in the format of `now_with_submillis`.
+static LAST_TIMESTAMP_GLOBAL: PgAtomic<AtomicU64> =
+    unsafe { PgAtomic::new(c"pgx_uuidv7_last_timestamp") };
+
+// Whether `LAST_TIMESTAMP_GLOBAL` lives in shared memory, i.e. the library was preloaded.
+static GLOBAL_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Request the shared memory used by `new_v7_monotonic_global`.
+/// This only has effect while `shared_preload_libraries` is being processed.
+pub(crate) fn init_shmem() {
+    if unsafe { pg_sys::process_shared_preload_libraries_in_progress } {
+        pg_shmem_init!(LAST_TIMESTAMP_GLOBAL);
+        GLOBAL_ENABLED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Fill a buffer with cryptographically strong random bytes from Postgres.
+pub(crate) fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut buf = [0u8; N];
+    if !unsafe { pg_sys::pg_strong_random(buf.as_mut_ptr().cast(), N) } {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INTERNAL_ERROR,
+            "could not generate random values"
+        );
+    }
+    buf
+}
+
+/// Generate a random UUIDv4.
+pub(crate) fn new_v4() -> uuid::Uuid {
+    uuid::Builder::from_random_bytes(random_bytes()).into_uuid()
+}
+
+/// Return the current time as unix milliseconds shifted left by `SUBMS_BITS`,
+/// with the sub-millisecond fraction in the low bits.
+fn now_with_submillis() -> u64 {
+    let d = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let millis = d.as_millis() as u64;
+    let submillis = ((d.subsec_nanos() % 1_000_000) as u64) << SUBMS_BITS;
+    (millis << SUBMS_BITS) | (submillis / 1_000_000)
+}
+
+/// Return the next timestamp to issue after `last`.
+/// This is `now` while the clock moves forward, and one sub-millisecond step
+/// past `last` when it stalls or goes backwards.
+#[inline]
+pub(crate) fn advance_timestamp(last: u64, now: u64) -> u64 {
+    if now > last {
+        now
+    } else {
+        last + 1
+    }
+}
+
+/// Build a UUIDv7 from a timestamp in the format of `now_with_submillis`.
+/// The sub-millisecond fraction goes to `rand_a`, `rand_b` is random.
+pub(crate) fn from_timestamp_with_submillis(ts: u64) -> uuid::Uuid {
+    let millis = ts >> SUBMS_BITS;
+    let rand_a = (ts & ((1 << SUBMS_BITS) - 1)) as u16;
+    let mut rv: [u8; 10] = random_bytes();
+    rv[..2].copy_from_slice(&rand_a.to_be_bytes());
+    uuid::Builder::from_unix_timestamp_millis(millis, &rv).into_uuid()
+}
+
+/// Generate a UUIDv7 for the current time that is strictly greater than
+/// every UUID previously generated by this function in the same backend.
+pub(crate) fn new_v7_monotonic() -> uuid::Uuid {
+    let ts = LAST_TIMESTAMP.with(|last| {
+        let ts = advance_timestamp(last.get(), now_with_submillis());
+        last.set(ts);
+        ts
+    });
+    from_timestamp_with_submillis(ts)
+}
+
+/// Generate a UUIDv7 for the current time that is strictly greater than
+/// every UUID previously generated by this function in the whole cluster.
+pub(crate) fn new_v7_monotonic_global() -> uuid::Uuid {
+    if !GLOBAL_ENABLED.load(Ordering::Relaxed) {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_OBJECT_NOT_IN_PREREQUISITE_STATE,
+            "pgx_uuidv7 must be loaded via shared_preload_libraries to generate cluster-wide monotonic UUIDs",
+            "Add pgx_uuidv7 to shared_preload_libraries and restart the server."
+        );
+    }
+    let now = now_with_submillis();
+    let last = LAST_TIMESTAMP_GLOBAL
+        .get()
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            Some(advance_timestamp(last, now))
+        })
+        .unwrap();
+    from_timestamp_with_submillis(advance_timestamp(last, now))
+}
+
+/// A fixed-length counter for UUIDv7 (RFC 9562 section 6.2 method 1).
+///
+/// The counter is seeded randomly whenever a different millisecond is requested,
+/// and incremented while the same millisecond is requested again.
+/// If it overflows, the millisecond is advanced by one and the counter is reseeded,
+/// so UUIDs generated for the same millisecond are always increasing.
+#[derive(Debug)]
+pub(crate) struct CounterV7 {
+    requested: Cell<Option<u64>>,
+    millis: Cell<u64>,
+    counter: Cell<u64>,
+}
+
+impl CounterV7 {
+    pub(crate) const fn new() -> Self {
+        CounterV7 {
+            requested: Cell::new(None),
+            millis: Cell::new(0),
+            counter: Cell::new(0),
+        }
+    }
+
+    /// Advance the state for the requested unix timestamp in milliseconds,
+    /// using `seed` if the counter has to be reseeded.
+    /// Return the millisecond and the counter to encode.
+    pub(crate) fn advance(&self, millis: u64, seed: u64) -> (u64, u64) {
+        let (millis_out, counter) = if self.requested.get() == Some(millis) {
+            match self.counter.get() {
+                COUNTER_MAX => (self.millis.get() + 1, seed),
+                c => (self.millis.get(), c + 1),
+            }
+        } else {
+            (millis, seed)
+        };
+        self.requested.set(Some(millis));
+        self.millis.set(millis_out);
+        self.counter.set(counter);
+        (millis_out, counter)
+    }
+
+    /// Generate a UUIDv7 for the given unix timestamp in milliseconds.
+    pub(crate) fn generate(&self, millis: u64) -> uuid::Uuid {
+        let seed = u64::from_be_bytes(random_bytes()) & COUNTER_SEED_MASK;
+        let (millis, counter) = self.advance(millis, seed);
+        from_millis_and_counter(millis, counter)
+    }
+}
+
+/// Build a UUIDv7 whose `rand_a` and the top 30 bits of `rand_b` hold `counter`.
+/// The remaining 32 bits of `rand_b` are random.
+pub(crate) fn from_millis_and_counter(millis: u64, counter: u64) -> uuid::Uuid {
+    let rand_a = (counter >> (COUNTER_BITS - 12)) as u16;
+    let rand_b = ((counter & 0x3fff_ffff) << 32) | u32::from_be_bytes(random_bytes()) as u64;
+    let mut rv = [0u8; 10];
+    rv[..2].copy_from_slice(&rand_a.to_be_bytes());
+    rv[2..].copy_from_slice(&rand_b.to_be_bytes());
+    uuid::Builder::from_unix_timestamp_millis(millis, &rv).into_uuid()
+}
+
+/// Generate a UUIDv7 for the given unix timestamp in milliseconds,
+/// using the counter of this backend.
+pub(crate) fn new_v7_counter(millis: u64) -> uuid::Uuid {
+    COUNTER.with(|counter| counter.generate(millis))
+}
+
+/// Generate `n` UUIDv7 for the given unix timestamp in milliseconds,
+/// using the counter of this backend.
+pub(crate) fn new_v7_counter_batch(millis: u64, n: usize) -> Vec<uuid::Uuid> {
+    COUNTER.with(|counter| {
+        (0..n)
+            .map(|_| {
+                pg_sys::check_for_interrupts!();
+                counter.generate(millis)
+            })
+            .collect()
+    })
+}
+
+/// Return the current time as 100-nanosecond ticks since the Gregorian epoch (1582-10-15).
+pub(crate) fn now_gregorian_ticks() -> u64 {
+    let d = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    uuid::Timestamp::from_unix(
+        uuid::timestamp::context::NoContext,
+        d.as_secs(),
+        d.subsec_nanos(),
+    )
+    .to_gregorian()
+    .0
+}
+
+/// The clock sequence of UUIDv1 (RFC 9562 section 5.1).
+///
+/// The clock sequence is seeded randomly, and incremented whenever the requested
+/// timestamp is not after the last one, so the same timestamp does not repeat a UUID.
+#[derive(Debug)]
+pub(crate) struct ClockSeq {
+    seq: Cell<Option<u16>>,
+    last_ticks: Cell<u64>,
+}
+
+impl ClockSeq {
+    pub(crate) const fn new() -> Self {
+        ClockSeq {
+            seq: Cell::new(None),
+            last_ticks: Cell::new(0),
+        }
+    }
+
+    /// Return the clock sequence for the requested timestamp in Gregorian ticks,
+    /// using `seed` if the clock sequence has not been set yet.
+    pub(crate) fn advance(&self, ticks: u64, seed: u16) -> u16 {
+        let seq = match self.seq.get() {
+            None => seed,
+            Some(seq) if ticks <= self.last_ticks.get() => seq.wrapping_add(1),
+            Some(seq) => seq,
+        } & 0x3fff;
+        self.seq.set(Some(seq));
+        self.last_ticks.set(ticks);
+        seq
+    }
+}
+
+/// Return the random node ID of this backend.
+/// The multicast bit is set, as RFC 9562 requires for node IDs that are not MAC addresses.
+pub(crate) fn random_node_id() -> [u8; 6] {
+    NODE_ID.with(|node_id| {
+        node_id.get().unwrap_or_else(|| {
+            let mut id: [u8; 6] = random_bytes();
+            id[0] |= 0x01;
+            node_id.set(Some(id));
+            id
+        })
+    })
+}
+
+// Return the clock sequence of this backend for the given timestamp in Gregorian ticks.
+fn next_clock_seq(ticks: u64) -> u16 {
+    let seed = u16::from_be_bytes(random_bytes());
+    CLOCK_SEQ.with(|clock_seq| clock_seq.advance(ticks, seed))
+}
+
+/// Generate a UUIDv1 for the given timestamp in Gregorian ticks and node ID,
+/// using the clock sequence of this backend.
+pub(crate) fn new_v1(ticks: u64, node_id: &[u8; 6]) -> uuid::Uuid {
+    uuid::Builder::from_gregorian_timestamp(ticks, next_clock_seq(ticks), node_id).into_uuid()
+}
+
+/// Generate a UUIDv6 for the given timestamp in Gregorian ticks and node ID,
+/// using the clock sequence of this backend.
+pub(crate) fn new_v6(ticks: u64, node_id: &[u8; 6]) -> uuid::Uuid {
+    uuid::Builder::from_sorted_gregorian_timestamp(ticks, next_clock_seq(ticks), node_id)
+        .into_uuid()
+}
+
+/// Reorder the timestamp of a UUIDv1 into the layout of UUIDv6.
+/// The clock sequence, the node and the variant are kept as they are.
+pub(crate) fn v1_to_v6(u: uuid::Uuid) -> uuid::Uuid {
+    let v = u.as_u128();
+    let time_low = v >> 96;
+    let time_mid = (v >> 80) & 0xffff;
+    let time_hi = (v >> 64) & 0x0fff;
+    let ticks = (time_hi << 48) | (time_mid << 32) | time_low;
+    let rest = v & 0xffff_ffff_ffff_ffff;
+    uuid::Uuid::from_u128(((ticks >> 12) << 80) | (0x6 << 76) | ((ticks & 0x0fff) << 64) | rest)
+}
+
+/// Reorder the timestamp of a UUIDv6 into the layout of UUIDv1.
+/// The clock sequence, the node and the variant are kept as they are.
+pub(crate) fn v6_to_v1(u: uuid::Uuid) -> uuid::Uuid {
+    let v = u.as_u128();
+    let ticks = ((v >> 80) << 12) | ((v >> 64) & 0x0fff);
+    let rest = v & 0xffff_ffff_ffff_ffff;
+    let time_low = ticks & 0xffff_ffff;
+    let time_mid = (ticks >> 32) & 0xffff;
+    let time_hi = ticks >> 48;
+    uuid::Uuid::from_u128(
+        (time_low << 96) | (time_mid << 80) | (0x1 << 76) | (time_hi << 64) | rest,
+    )
+}
@@ -0,0 +1,52 @@
+use pgrx::{GucContext, GucFlags, GucRegistry, GucSetting, PostgresGucEnum};
+
+/// What to do with a timestamp outside the 48-bit millisecond range of UUIDv7,
+/// 1970-01-01 00:00:00 UTC to 10889-08-02 05:31:50.655 UTC.
+#[derive(PostgresGucEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TimestampOutOfRange {
+    /// Raise an error with SQLSTATE 22008 (datetime_field_overflow).
+    #[name = c"error"]
+    Error,
+    /// Use the nearest end of the range.
+    #[name = c"clamp"]
+    Clamp,
+    /// Keep the low 48 bits of the milliseconds, as RFC 9562 allows for rollover.
+    #[name = c"wrap"]
+    Wrap,
+}
+
+pub(crate) static TIMESTAMP_OUT_OF_RANGE: GucSetting<TimestampOutOfRange> =
+    GucSetting::<TimestampOutOfRange>::new(TimestampOutOfRange::Error);
+
+/// How precisely UUIDv7 generated for a given timestamp encode it.
+#[derive(PostgresGucEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TimestampPrecision {
+    /// Only the milliseconds, `rand_a` is random.
+    #[name = c"millisecond"]
+    Millisecond,
+    /// The microseconds within the millisecond are also encoded in `rand_a` (RFC 9562 method 3).
+    #[name = c"microsecond"]
+    Microsecond,
+}
+
+pub(crate) static TIMESTAMP_PRECISION: GucSetting<TimestampPrecision> =
+    GucSetting::<TimestampPrecision>::new(TimestampPrecision::Millisecond);
+
+pub(crate) fn init() {
+    GucRegistry::define_enum_guc(
+        c"pgx_uuidv7.timestamp_out_of_range",
+        c"What to do when a UUIDv7 is generated for a timestamp outside its 48-bit range.",
+        c"error raises an error, clamp uses the nearest end of the range, and wrap keeps the low 48 bits of the milliseconds.",
+        &TIMESTAMP_OUT_OF_RANGE,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+    GucRegistry::define_enum_guc(
+        c"pgx_uuidv7.timestamp_precision",
+        c"How precisely a UUIDv7 generated for a given timestamp encodes it.",
+        c"millisecond keeps rand_a random, and microsecond also encodes the microseconds in rand_a so uuid_to_timestamptz_precise can decode them.",
+        &TIMESTAMP_PRECISION,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+}
@@ -0,0 +1,85 @@
+use crate::my_converter::{from_unix_micros, uuid_timestamp_unix_micros, Converter};
+use pgrx::prelude::*;
+
+/// Return the name of the variant of a UUID.
+fn variant_name(u: &uuid::Uuid) -> &'static str {
+    match u.get_variant() {
+        uuid::Variant::NCS => "NCS",
+        uuid::Variant::RFC4122 => "RFC",
+        uuid::Variant::Microsoft => "Microsoft",
+        _ => "Future",
+    }
+}
+
+extension_sql!(
+    r#"
+CREATE TYPE uuid_fields AS (
+    version smallint,
+    variant text,
+    ts timestamptz,
+    rand_a smallint,
+    rand_b bigint,
+    clock_seq smallint,
+    node bytea,
+    is_nil bool,
+    is_max bool
+);
+
+COMMENT ON TYPE uuid_fields IS 'The fields of a UUID, as decoded by uuid_inspect.';
+"#,
+    name = "type_uuid_fields",
+);
+
+/// Decode the fields of a UUID.
+/// The timestamp is set for v1, v6 and v7, `rand_a` and `rand_b` for v7,
+/// and the clock sequence and node for v1 and v6, when the variant is RFC.
+#[pg_extern(immutable, parallel_safe, requires = ["type_uuid_fields"])]
+fn uuid_inspect(uuid: pgrx::Uuid) -> pgrx::composite_type!('static, "uuid_fields") {
+    let u: uuid::Uuid = Converter(uuid).into();
+    let v = u.as_u128();
+    let rfc = u.get_variant() == uuid::Variant::RFC4122;
+    let version = u.get_version_num();
+
+    let ts = if rfc {
+        uuid_timestamp_unix_micros(&u).map(from_unix_micros)
+    } else {
+        None
+    };
+    let (rand_a, rand_b) = if rfc && version == 7 {
+        (
+            Some(((v >> 64) & 0x0fff) as i16),
+            Some((v & 0x3fff_ffff_ffff_ffff) as i64),
+        )
+    } else {
+        (None, None)
+    };
+    let (clock_seq, node) = if rfc && (version == 1 || version == 6) {
+        (
+            Some(((v >> 48) & 0x3fff) as i16),
+            u.get_node_id().map(|node| node.to_vec()),
+        )
+    } else {
+        (None, None)
+    };
+
+    let mut fields = PgHeapTuple::new_composite_type("uuid_fields").unwrap();
+    fields.set_by_name("version", version as i16).unwrap();
+    fields.set_by_name("variant", variant_name(&u)).unwrap();
+    fields.set_by_name("ts", ts).unwrap();
+    fields.set_by_name("rand_a", rand_a).unwrap();
+    fields.set_by_name("rand_b", rand_b).unwrap();
+    fields.set_by_name("clock_seq", clock_seq).unwrap();
+    fields.set_by_name("node", node).unwrap();
+    fields.set_by_name("is_nil", u.is_nil()).unwrap();
+    fields.set_by_name("is_max", u.is_max()).unwrap();
+    fields
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_inspect"(uuid)
+IS 'Decode the fields of a UUID. The timestamp is set for v1, v6 and v7, rand_a and rand_b for v7, and the clock sequence and node for v1 and v6, when the variant is RFC.';
+"#,
+    name = "comment_uuid_inspect",
+    requires = [uuid_inspect],
+);
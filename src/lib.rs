@@ -1,28 +1,165 @@
+mod encoding;
+mod generator;
+mod guc;
+mod inspect;
 mod my_converter;
+mod typeid;
+mod ulid;
+mod uuidrange;
+mod uuidv7_type;
 
-use my_converter::{to_uuid_timestamp_buildpart, Converter};
+use my_converter::{
+    cmp_uuid_v7_range, decode_submillis_micros, encode_submillis_micros, from_unix_micros,
+    to_uuid_gregorian_ticks, to_uuid_timestamp_buildpart, to_uuid_timestamp_buildpart_with,
+    to_uuid_timestamp_micros, uuid_timestamp_unix_micros, Converter,
+};
 use pgrx::prelude::*;
 use uuid::Uuid;
 
 pgrx::pg_module_magic!();
 
+#[pg_guard]
+pub extern "C-unwind" fn _PG_init() {
+    guc::init();
+    generator::init_shmem();
+}
+
 /// Return the version of given uuid.
+/// Return NULL for the nil and max UUIDs, which have no version.
 #[pg_extern(parallel_safe)]
-fn uuid_get_version(uuid: pgrx::Uuid) -> i8 {
+fn uuid_get_version(uuid: pgrx::Uuid) -> Option<i8> {
     let u: uuid::Uuid = Converter(uuid).into();
+    if u.is_nil() || u.is_max() {
+        return None;
+    }
     let v = u.get_version_num();
-    v as i8
+    Some(v as i8)
 }
 
 extension_sql!(
     r#"
 COMMENT ON FUNCTION "uuid_get_version"(uuid)
-IS 'Return the version of given uuid.';
+IS 'Return the version of given uuid. Return NULL for the nil and max UUIDs, which have no version.';
 "#,
     name = "comment_uuid_get_version",
     requires = [uuid_get_version],
 );
 
+/// Return the nil UUID, with all bits set to zero.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_nil() -> pgrx::Uuid {
+    Converter(Uuid::nil()).into()
+}
+
+/// Return the max UUID, with all bits set to one.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_max() -> pgrx::Uuid {
+    Converter(Uuid::max()).into()
+}
+
+/// Return whether the given uuid is the nil UUID.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_nil(uuid: pgrx::Uuid) -> bool {
+    Uuid::from(Converter(uuid)).is_nil()
+}
+
+/// Return whether the given uuid is the max UUID.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_max(uuid: pgrx::Uuid) -> bool {
+    Uuid::from(Converter(uuid)).is_max()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_nil"()
+IS 'Return the nil UUID, with all bits set to zero. It sorts before every other UUID.';
+COMMENT ON FUNCTION "uuid_max"()
+IS 'Return the max UUID, with all bits set to one. It sorts after every other UUID.';
+COMMENT ON FUNCTION "uuid_is_nil"(uuid)
+IS 'Return whether the given uuid is the nil UUID.';
+COMMENT ON FUNCTION "uuid_is_max"(uuid)
+IS 'Return whether the given uuid is the max UUID.';
+"#,
+    name = "comment_uuid_nil_max",
+    requires = [uuid_nil, uuid_max, uuid_is_nil, uuid_is_max],
+);
+
+/// Return whether the given UUID has the given version and the RFC 9562 variant.
+pub(crate) fn is_valid_version(u: &uuid::Uuid, version: usize) -> bool {
+    u.get_version_num() == version && u.get_variant() == uuid::Variant::RFC4122
+}
+
+/// Return whether the given uuid is version 1 with the RFC 9562 variant.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_valid_v1(uuid: pgrx::Uuid) -> bool {
+    is_valid_version(&Converter(uuid).into(), 1)
+}
+
+/// Return whether the given uuid is version 3 with the RFC 9562 variant.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_valid_v3(uuid: pgrx::Uuid) -> bool {
+    is_valid_version(&Converter(uuid).into(), 3)
+}
+
+/// Return whether the given uuid is version 4 with the RFC 9562 variant.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_valid_v4(uuid: pgrx::Uuid) -> bool {
+    is_valid_version(&Converter(uuid).into(), 4)
+}
+
+/// Return whether the given uuid is version 5 with the RFC 9562 variant.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_valid_v5(uuid: pgrx::Uuid) -> bool {
+    is_valid_version(&Converter(uuid).into(), 5)
+}
+
+/// Return whether the given uuid is version 6 with the RFC 9562 variant.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_valid_v6(uuid: pgrx::Uuid) -> bool {
+    is_valid_version(&Converter(uuid).into(), 6)
+}
+
+/// Return whether the given uuid is version 7 with the RFC 9562 variant.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_valid_v7(uuid: pgrx::Uuid) -> bool {
+    is_valid_version(&Converter(uuid).into(), 7)
+}
+
+/// Return whether the given uuid is version 8 with the RFC 9562 variant.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_is_valid_v8(uuid: pgrx::Uuid) -> bool {
+    is_valid_version(&Converter(uuid).into(), 8)
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_is_valid_v1"(uuid)
+IS 'Return whether the given uuid is version 1 with the RFC 9562 variant.';
+COMMENT ON FUNCTION "uuid_is_valid_v3"(uuid)
+IS 'Return whether the given uuid is version 3 with the RFC 9562 variant.';
+COMMENT ON FUNCTION "uuid_is_valid_v4"(uuid)
+IS 'Return whether the given uuid is version 4 with the RFC 9562 variant.';
+COMMENT ON FUNCTION "uuid_is_valid_v5"(uuid)
+IS 'Return whether the given uuid is version 5 with the RFC 9562 variant.';
+COMMENT ON FUNCTION "uuid_is_valid_v6"(uuid)
+IS 'Return whether the given uuid is version 6 with the RFC 9562 variant.';
+COMMENT ON FUNCTION "uuid_is_valid_v7"(uuid)
+IS 'Return whether the given uuid is version 7 with the RFC 9562 variant.';
+COMMENT ON FUNCTION "uuid_is_valid_v8"(uuid)
+IS 'Return whether the given uuid is version 8 with the RFC 9562 variant.';
+"#,
+    name = "comment_uuid_is_valid",
+    requires = [
+        uuid_is_valid_v1,
+        uuid_is_valid_v3,
+        uuid_is_valid_v4,
+        uuid_is_valid_v5,
+        uuid_is_valid_v6,
+        uuid_is_valid_v7,
+        uuid_is_valid_v8
+    ],
+);
+
 /// Generate and return a new UUID using the v7 algorithm.
 /// The timestamp is the current time.
 #[pg_extern(parallel_safe)]
@@ -39,23 +176,505 @@ IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the
     requires = [uuid_generate_v7_now],
 );
 
+/// Generate and return a new UUID using the v7 algorithm.
+/// The timestamp is the current time with sub-millisecond precision.
+/// UUIDs generated in the same session are strictly increasing.
+/// The last timestamp is kept in the backend, so the function is parallel restricted:
+/// parallel workers would each keep their own.
+#[pg_extern(parallel_restricted)]
+fn uuid_generate_v7_monotonic() -> pgrx::Uuid {
+    Converter(generator::new_v7_monotonic()).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v7_monotonic"()
+IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the current time with sub-millisecond precision. UUIDs generated in the same session are strictly increasing. Parallel restricted, since the last timestamp is kept in the backend.';
+"#,
+    name = "comment_uuid_generate_v7_monotonic",
+    requires = [uuid_generate_v7_monotonic],
+);
+
+/// Generate and return a new UUID using the v7 algorithm.
+/// The timestamp is the current time with sub-millisecond precision.
+/// UUIDs generated in the whole cluster are strictly increasing.
+/// Requires pgx_uuidv7 in shared_preload_libraries.
+#[pg_extern(parallel_safe)]
+fn uuid_generate_v7_monotonic_global() -> pgrx::Uuid {
+    Converter(generator::new_v7_monotonic_global()).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v7_monotonic_global"()
+IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the current time with sub-millisecond precision. UUIDs generated in the whole cluster are strictly increasing. Requires pgx_uuidv7 in shared_preload_libraries.';
+"#,
+    name = "comment_uuid_generate_v7_monotonic_global",
+    requires = [uuid_generate_v7_monotonic_global],
+);
+
 /// Generate and return a new UUID using the v7 algorithm.
 /// The timestamp is the given timestamp.
 #[pg_extern(parallel_safe)]
 fn uuid_generate_v7(ts: pgrx::datum::TimestampWithTimeZone) -> pgrx::Uuid {
-    let u = Uuid::new_v7(Converter(ts).into());
+    let micros = to_uuid_timestamp_micros(ts);
+    let mut rv: [u8; 10] = generator::random_bytes();
+    if guc::TIMESTAMP_PRECISION.get() == guc::TimestampPrecision::Microsecond {
+        rv[..2].copy_from_slice(&encode_submillis_micros(micros).to_be_bytes());
+    }
+    let u = uuid::Builder::from_unix_timestamp_millis(micros / 1_000, &rv).into_uuid();
     Converter(u).into()
 }
 
 extension_sql!(
     r#"
 COMMENT ON FUNCTION "uuid_generate_v7"(timestamptz)
-IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the given timestamp.';
+IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the given timestamp. With pgx_uuidv7.timestamp_precision = microsecond, the microseconds are also encoded in rand_a.';
 "#,
     name = "comment_uuid_generate_v7",
     requires = [uuid_generate_v7],
 );
 
+/// Generate and return a new UUID using the v7 algorithm.
+/// The timestamp is the given timestamp.
+/// A counter that is seeded randomly for each new millisecond fills `rand_a`
+/// and the top bits of `rand_b`, so that UUIDs generated for the same millisecond
+/// in the same session are strictly increasing.
+/// The counter is kept in the backend, so the function is parallel restricted.
+#[pg_extern(parallel_restricted)]
+fn uuid_generate_v7_counter(ts: pgrx::datum::TimestampWithTimeZone) -> pgrx::Uuid {
+    Converter(generator::new_v7_counter(to_uuid_timestamp_buildpart(ts))).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v7_counter"(timestamptz)
+IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the given timestamp. A counter that is seeded randomly for each new millisecond fills rand_a and the top bits of rand_b, so that UUIDs generated for the same millisecond in the same session are strictly increasing. Parallel restricted, since the counter is kept in the backend.';
+"#,
+    name = "comment_uuid_generate_v7_counter",
+    requires = [uuid_generate_v7_counter],
+);
+
+// The most UUIDs that fit in one Postgres allocation, MaxAllocSize of 1 GB - 1.
+// Larger batches are rejected before the Rust vector is allocated.
+const MAX_BATCH_SIZE: i32 = (0x3fff_ffff / 16) as i32;
+
+/// Return `n` as a batch size, raising an error if it is negative or too large for an array.
+fn check_batch_size(n: i32) -> usize {
+    if n < 0 {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            "n must not be negative"
+        );
+    }
+    if n > MAX_BATCH_SIZE {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_PROGRAM_LIMIT_EXCEEDED,
+            format!("n must not exceed {MAX_BATCH_SIZE}")
+        );
+    }
+    n as usize
+}
+
+/// Generate and return an array of `n` new UUIDs using the v7 algorithm.
+/// The timestamp is the given timestamp, or the current time by default.
+/// The UUIDs take consecutive values of the counter of `uuid_generate_v7_counter`,
+/// so they are strictly increasing.
+/// `n` is at most 67108863, the most UUIDs that fit in one array.
+/// The counter is kept in the backend, so the function is parallel restricted.
+#[pg_extern(parallel_restricted)]
+fn uuid_generate_v7_batch(
+    n: i32,
+    ts: default!(pgrx::datum::TimestampWithTimeZone, "now()"),
+) -> Vec<pgrx::Uuid> {
+    let n = check_batch_size(n);
+    generator::new_v7_counter_batch(to_uuid_timestamp_buildpart(ts), n)
+        .into_iter()
+        .map(|u| Converter(u).into())
+        .collect()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v7_batch"(integer, timestamptz)
+IS 'Generate and return an array of n new UUIDs using the v7 algorithm. The timestamp is the given timestamp, or the current time by default. The UUIDs take consecutive values of the counter of uuid_generate_v7_counter, so they are strictly increasing. n is at most 67108863, the most UUIDs that fit in one array. Parallel restricted, since the counter is kept in the backend.';
+"#,
+    name = "comment_uuid_generate_v7_batch",
+    requires = [uuid_generate_v7_batch],
+);
+
+/// Generate and return an array of `n` new UUIDs using the v4 algorithm.
+/// `n` is at most 67108863, the most UUIDs that fit in one array.
+#[pg_extern(parallel_safe)]
+fn uuid_generate_v4_batch(n: i32) -> Vec<pgrx::Uuid> {
+    (0..check_batch_size(n))
+        .map(|_| {
+            pg_sys::check_for_interrupts!();
+            Converter(generator::new_v4()).into()
+        })
+        .collect()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v4_batch"(integer)
+IS 'Generate and return an array of n new UUIDs using the v4 algorithm. n is at most 67108863, the most UUIDs that fit in one array.';
+"#,
+    name = "comment_uuid_generate_v4_batch",
+    requires = [uuid_generate_v4_batch],
+);
+
+/// Generate a series of new UUIDs using the v7 algorithm.
+/// The timestamps go from `start` to `stop` by `step`, like `generate_series`,
+/// and `per_step` UUIDs are generated for each timestamp.
+/// UUIDs generated for the same millisecond are strictly increasing.
+#[pg_extern(parallel_safe)]
+fn uuid_generate_v7_series(
+    start: pgrx::datum::TimestampWithTimeZone,
+    stop: pgrx::datum::TimestampWithTimeZone,
+    step: pgrx::datum::Interval,
+    per_step: default!(i32, 1),
+) -> SetOfIterator<'static, pgrx::Uuid> {
+    let step_micros = step.as_micros();
+    if step_micros == 0 {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            "step size cannot equal zero"
+        );
+    }
+    if per_step < 1 {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            "per_step must be positive"
+        );
+    }
+    let counter = generator::CounterV7::new();
+    let timestamps =
+        std::iter::successors(Some(start), move |ts| Some(*ts + step)).take_while(move |ts| {
+            if step_micros > 0 {
+                *ts <= stop
+            } else {
+                *ts >= stop
+            }
+        });
+    SetOfIterator::new(
+        timestamps
+            .flat_map(move |ts| {
+                let millis = to_uuid_timestamp_buildpart(ts);
+                (0..per_step).map(move |_| millis)
+            })
+            .map(move |millis| Converter(counter.generate(millis)).into()),
+    )
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v7_series"(timestamptz, timestamptz, interval, integer)
+IS 'Generate a series of new UUIDs using the v7 algorithm. The timestamps go from start to stop by step, like generate_series, and per_step UUIDs are generated for each timestamp. UUIDs generated for the same millisecond are strictly increasing.';
+"#,
+    name = "comment_uuid_generate_v7_series",
+    requires = [uuid_generate_v7_series],
+);
+
+/// Return the node ID to use for UUIDv1 and UUIDv6:
+/// the given 6 bytes, or the random node ID of the session if NULL.
+fn node_id_or_random(node: Option<&[u8]>) -> [u8; 6] {
+    match node {
+        None => generator::random_node_id(),
+        Some(node) => {
+            if node.len() != 6 {
+                ereport!(
+                    ERROR,
+                    PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+                    "node must be 6 bytes long",
+                    format!("The given node is {} bytes long.", node.len())
+                );
+            }
+            node.try_into().unwrap()
+        }
+    }
+}
+
+/// Generate and return a new UUID using the v1 algorithm.
+/// The timestamp is the current time, and the node is the random node ID of the session.
+/// The clock sequence and the node ID are kept in the backend, so the function is parallel restricted.
+#[pg_extern(parallel_restricted)]
+fn uuid_generate_v1_now() -> pgrx::Uuid {
+    let u = generator::new_v1(generator::now_gregorian_ticks(), &node_id_or_random(None));
+    Converter(u).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v1_now"()
+IS 'Generate and return a new UUID using the v1 algorithm. The timestamp is the current time, and the node is the random node ID of the session.';
+"#,
+    name = "comment_uuid_generate_v1_now",
+    requires = [uuid_generate_v1_now],
+);
+
+/// Generate and return a new UUID using the v1 algorithm.
+/// The timestamp is the given timestamp.
+/// The node is the given 6 bytes, or the random node ID of the session if NULL.
+/// The clock sequence is kept in the session.
+/// Return NULL if the timestamp is NULL; the function is not strict because of the optional node.
+/// The clock sequence and the node ID are kept in the backend, so the function is parallel restricted.
+#[pg_extern(parallel_restricted)]
+fn uuid_generate_v1(
+    ts: Option<pgrx::datum::TimestampWithTimeZone>,
+    node: default!(Option<&[u8]>, "NULL"),
+) -> Option<pgrx::Uuid> {
+    let u = generator::new_v1(to_uuid_gregorian_ticks(ts?), &node_id_or_random(node));
+    Some(Converter(u).into())
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v1"(timestamptz, bytea)
+IS 'Generate and return a new UUID using the v1 algorithm. The timestamp is the given timestamp. The node is the given 6 bytes, or the random node ID of the session if NULL. The clock sequence is kept in the session. Return NULL if the timestamp is NULL.';
+"#,
+    name = "comment_uuid_generate_v1",
+    requires = [uuid_generate_v1],
+);
+
+/// Generate and return a new UUID using the v6 algorithm.
+/// The timestamp is the current time, and the node is the random node ID of the session.
+/// The clock sequence and the node ID are kept in the backend, so the function is parallel restricted.
+#[pg_extern(parallel_restricted)]
+fn uuid_generate_v6_now() -> pgrx::Uuid {
+    let u = generator::new_v6(generator::now_gregorian_ticks(), &node_id_or_random(None));
+    Converter(u).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v6_now"()
+IS 'Generate and return a new UUID using the v6 algorithm. The timestamp is the current time, and the node is the random node ID of the session.';
+"#,
+    name = "comment_uuid_generate_v6_now",
+    requires = [uuid_generate_v6_now],
+);
+
+/// Generate and return a new UUID using the v6 algorithm.
+/// The timestamp is the given timestamp.
+/// The node is the given 6 bytes, or the random node ID of the session if NULL.
+/// The clock sequence is kept in the session.
+/// Return NULL if the timestamp is NULL; the function is not strict because of the optional node.
+/// The clock sequence and the node ID are kept in the backend, so the function is parallel restricted.
+#[pg_extern(parallel_restricted)]
+fn uuid_generate_v6(
+    ts: Option<pgrx::datum::TimestampWithTimeZone>,
+    node: default!(Option<&[u8]>, "NULL"),
+) -> Option<pgrx::Uuid> {
+    let u = generator::new_v6(to_uuid_gregorian_ticks(ts?), &node_id_or_random(node));
+    Some(Converter(u).into())
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v6"(timestamptz, bytea)
+IS 'Generate and return a new UUID using the v6 algorithm. The timestamp is the given timestamp. The node is the given 6 bytes, or the random node ID of the session if NULL. The clock sequence is kept in the session. Return NULL if the timestamp is NULL.';
+"#,
+    name = "comment_uuid_generate_v6",
+    requires = [uuid_generate_v6],
+);
+
+/// Raise an error unless the given uuid has the expected version.
+fn check_version(u: uuid::Uuid, expected: usize) {
+    let v = u.get_version_num();
+    if v != expected {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            format!("invalid version of uuid: \"{u}\""),
+            format!("UUID version is {v}, expected {expected}.")
+        );
+    }
+}
+
+/// Convert a UUIDv1 to a UUIDv6 with the same timestamp, clock sequence and node.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_v1_to_v6(uuid: pgrx::Uuid) -> pgrx::Uuid {
+    let u: uuid::Uuid = Converter(uuid).into();
+    check_version(u, 1);
+    Converter(generator::v1_to_v6(u)).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_v1_to_v6"(uuid)
+IS 'Convert a UUIDv1 to a UUIDv6 with the same timestamp, clock sequence and node.';
+"#,
+    name = "comment_uuid_v1_to_v6",
+    requires = [uuid_v1_to_v6],
+);
+
+/// Convert a UUIDv6 to a UUIDv1 with the same timestamp, clock sequence and node.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_v6_to_v1(uuid: pgrx::Uuid) -> pgrx::Uuid {
+    let u: uuid::Uuid = Converter(uuid).into();
+    check_version(u, 6);
+    Converter(generator::v6_to_v1(u)).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_v6_to_v1"(uuid)
+IS 'Convert a UUIDv6 to a UUIDv1 with the same timestamp, clock sequence and node.';
+"#,
+    name = "comment_uuid_v6_to_v1",
+    requires = [uuid_v6_to_v1],
+);
+
+/// Return the namespace UUID for fully-qualified domain names.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_ns_dns() -> pgrx::Uuid {
+    Converter(Uuid::NAMESPACE_DNS).into()
+}
+
+/// Return the namespace UUID for URLs.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_ns_url() -> pgrx::Uuid {
+    Converter(Uuid::NAMESPACE_URL).into()
+}
+
+/// Return the namespace UUID for ISO object identifiers.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_ns_oid() -> pgrx::Uuid {
+    Converter(Uuid::NAMESPACE_OID).into()
+}
+
+/// Return the namespace UUID for X.500 distinguished names.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_ns_x500() -> pgrx::Uuid {
+    Converter(Uuid::NAMESPACE_X500).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_ns_dns"()
+IS 'Return the namespace UUID for fully-qualified domain names.';
+COMMENT ON FUNCTION "uuid_ns_url"()
+IS 'Return the namespace UUID for URLs.';
+COMMENT ON FUNCTION "uuid_ns_oid"()
+IS 'Return the namespace UUID for ISO object identifiers.';
+COMMENT ON FUNCTION "uuid_ns_x500"()
+IS 'Return the namespace UUID for X.500 distinguished names.';
+"#,
+    name = "comment_uuid_ns",
+    requires = [uuid_ns_dns, uuid_ns_url, uuid_ns_oid, uuid_ns_x500],
+);
+
+/// Generate and return a UUID using the v3 algorithm (MD5).
+/// The UUID is derived from the given namespace and name.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_generate_v3(namespace: pgrx::Uuid, name: &str) -> pgrx::Uuid {
+    uuid_generate_v3_bytea(namespace, name.as_bytes())
+}
+
+/// Generate and return a UUID using the v3 algorithm (MD5).
+/// The UUID is derived from the given namespace and name.
+#[pg_extern(name = "uuid_generate_v3", immutable, parallel_safe)]
+fn uuid_generate_v3_bytea(namespace: pgrx::Uuid, name: &[u8]) -> pgrx::Uuid {
+    let ns: uuid::Uuid = Converter(namespace).into();
+    Converter(Uuid::new_v3(&ns, name)).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v3"(uuid, text)
+IS 'Generate and return a UUID using the v3 algorithm (MD5). The UUID is derived from the given namespace and name.';
+COMMENT ON FUNCTION "uuid_generate_v3"(uuid, bytea)
+IS 'Generate and return a UUID using the v3 algorithm (MD5). The UUID is derived from the given namespace and name.';
+"#,
+    name = "comment_uuid_generate_v3",
+    requires = [uuid_generate_v3, uuid_generate_v3_bytea],
+);
+
+/// Generate and return a UUID using the v5 algorithm (SHA-1).
+/// The UUID is derived from the given namespace and name.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_generate_v5(namespace: pgrx::Uuid, name: &str) -> pgrx::Uuid {
+    uuid_generate_v5_bytea(namespace, name.as_bytes())
+}
+
+/// Generate and return a UUID using the v5 algorithm (SHA-1).
+/// The UUID is derived from the given namespace and name.
+#[pg_extern(name = "uuid_generate_v5", immutable, parallel_safe)]
+fn uuid_generate_v5_bytea(namespace: pgrx::Uuid, name: &[u8]) -> pgrx::Uuid {
+    let ns: uuid::Uuid = Converter(namespace).into();
+    Converter(Uuid::new_v5(&ns, name)).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v5"(uuid, text)
+IS 'Generate and return a UUID using the v5 algorithm (SHA-1). The UUID is derived from the given namespace and name.';
+COMMENT ON FUNCTION "uuid_generate_v5"(uuid, bytea)
+IS 'Generate and return a UUID using the v5 algorithm (SHA-1). The UUID is derived from the given namespace and name.';
+"#,
+    name = "comment_uuid_generate_v5",
+    requires = [uuid_generate_v5, uuid_generate_v5_bytea],
+);
+
+/// Raise an error unless `value` fits in `bits` unsigned bits.
+fn check_field_range(field: &str, value: i64, bits: u32) -> u64 {
+    if value < 0 || value >= 1 << bits {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_NUMERIC_VALUE_OUT_OF_RANGE,
+            format!("{field} is out of range for UUIDv8: {value}"),
+            format!("{field} must be between 0 and {}.", (1i64 << bits) - 1)
+        );
+    }
+    value as u64
+}
+
+/// Generate and return a UUID using the v8 layout.
+/// `custom_a` fills the first 48 bits, `custom_b` the 12 bits after the version,
+/// and `custom_c` the 62 bits after the variant.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_generate_v8(custom_a: i64, custom_b: i16, custom_c: i64) -> pgrx::Uuid {
+    let a = check_field_range("custom_a", custom_a, 48);
+    let b = check_field_range("custom_b", custom_b as i64, 12);
+    let c = check_field_range("custom_c", custom_c, 62);
+    let mut bytes = [0u8; 16];
+    bytes[..6].copy_from_slice(&a.to_be_bytes()[2..]);
+    bytes[6..8].copy_from_slice(&(b as u16).to_be_bytes());
+    bytes[8..].copy_from_slice(&c.to_be_bytes());
+    Converter(uuid::Builder::from_custom_bytes(bytes).into_uuid()).into()
+}
+
+/// Generate and return a UUID using the v8 layout from 16 bytes.
+/// The version and variant bits of the given bytes are overwritten.
+#[pg_extern(name = "uuid_generate_v8", immutable, parallel_safe)]
+fn uuid_generate_v8_bytea(bytes: &[u8]) -> pgrx::Uuid {
+    if bytes.len() != 16 {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            "custom bytes for UUIDv8 must be 16 bytes long",
+            format!("The given bytes are {} bytes long.", bytes.len())
+        );
+    }
+    Converter(uuid::Builder::from_custom_bytes(bytes.try_into().unwrap()).into_uuid()).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_generate_v8"(bigint, smallint, bigint)
+IS 'Generate and return a UUID using the v8 layout. custom_a fills the first 48 bits, custom_b the 12 bits after the version, and custom_c the 62 bits after the variant.';
+COMMENT ON FUNCTION "uuid_generate_v8"(bytea)
+IS 'Generate and return a UUID using the v8 layout from 16 bytes. The version and variant bits of the given bytes are overwritten.';
+"#,
+    name = "comment_uuid_generate_v8",
+    requires = [uuid_generate_v8, uuid_generate_v8_bytea],
+);
+
 /// Generate and return a new UUID using the v7 algorithm.
 /// The timestamp is the current time plus the given interval.
 #[pg_extern(parallel_safe)]
@@ -85,7 +704,7 @@ IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the
 #[pg_extern(immutable, parallel_safe)]
 fn uuid_to_timestamptz(uuid: pgrx::Uuid) -> Option<pgrx::datum::TimestampWithTimeZone> {
     let u: uuid::Uuid = Converter(uuid).into();
-    u.get_timestamp().map(|ts| Converter(ts).into())
+    uuid_timestamp_unix_micros(&u).map(from_unix_micros)
 }
 
 extension_sql!(
@@ -97,6 +716,56 @@ IS 'Convert a UUID to a timestamptz. The timestamp is the timestamp encoded in t
     requires = [uuid_to_timestamptz],
 );
 
+/// Convert a UUID to a timestamptz, with the microseconds encoded in `rand_a` of a UUIDv7.
+/// This is exact for UUIDv7 generated with `pgx_uuidv7.timestamp_precision = microsecond`,
+/// and is the same as `uuid_to_timestamptz` for the other versions.
+/// The timezone is UTC.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_to_timestamptz_precise(uuid: pgrx::Uuid) -> Option<pgrx::datum::TimestampWithTimeZone> {
+    let u: uuid::Uuid = Converter(uuid).into();
+    let micros = uuid_timestamp_unix_micros(&u)?;
+    if u.get_version_num() != 7 {
+        return Some(from_unix_micros(micros));
+    }
+    let rand_a = (u.as_u128() >> 64) as u16;
+    Some(from_unix_micros(
+        micros + decode_submillis_micros(rand_a) as i128,
+    ))
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_to_timestamptz_precise"(uuid)
+IS 'Convert a UUID to a timestamptz, with the microseconds encoded in rand_a of a UUIDv7. This is exact for UUIDv7 generated with pgx_uuidv7.timestamp_precision = microsecond, and is the same as uuid_to_timestamptz for the other versions. The timezone is UTC.';
+"#,
+    name = "comment_uuid_to_timestamptz_precise",
+    requires = [uuid_to_timestamptz_precise],
+);
+
+/// Convert a UUID to the millisecond that contains its timestamp, as a tstzrange.
+/// The range is half-open, `[ms, ms + 1ms)`.
+/// Return NULL if the UUID has no timestamp.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_to_tstzrange(
+    uuid: pgrx::Uuid,
+) -> Option<pgrx::datum::Range<pgrx::datum::TimestampWithTimeZone>> {
+    let u: uuid::Uuid = Converter(uuid).into();
+    uuid_timestamp_unix_micros(&u).map(|micros| {
+        let lower = from_unix_micros(micros - micros.rem_euclid(1_000));
+        let upper = lower + pgrx::datum::Interval::from_micros(1_000);
+        (lower..upper).into()
+    })
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_to_tstzrange"(uuid)
+IS 'Convert a UUID to the millisecond that contains its timestamp, as a tstzrange. The range is half-open, [ms, ms + 1ms). Return NULL if the UUID has no timestamp.';
+"#,
+    name = "comment_uuid_to_tstzrange",
+    requires = [uuid_to_tstzrange],
+);
+
 /// Generate and return a new UUID using the v7 algorithm.
 /// The timestamp is the given timestamp.
 /// This function is a wrapper around `uuid_generate_v7`.
@@ -114,16 +783,20 @@ IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the
     requires = [timestamptz_to_uuid_v7_random],
 );
 
+// The bounds are immutable, so they do not follow pgx_uuidv7.timestamp_out_of_range
+// and always raise an error for a timestamp outside the range of UUIDv7.
 #[inline]
 fn _timestamptz_to_uuid_v7(ts: pgrx::datum::TimestampWithTimeZone, rv: &[u8; 10]) -> pgrx::Uuid {
-    let u: uuid::Uuid =
-        uuid::Builder::from_unix_timestamp_millis(to_uuid_timestamp_buildpart(ts), rv).into_uuid();
+    let millis = to_uuid_timestamp_buildpart_with(ts, guc::TimestampOutOfRange::Error);
+    let u: uuid::Uuid = uuid::Builder::from_unix_timestamp_millis(millis, rv).into_uuid();
     Converter(u).into()
 }
 
 /// Generate and return a new UUID using the v7 algorithm.
 /// The timestamp is the given timestamp.
 /// The UUID is the minimum UUID that can be generated for the given timestamp.
+/// At 1970-01-01 00:00:00 UTC, the start of the 48-bit timestamp range,
+/// it is 00000000-0000-7000-8000-000000000000, which still sorts after the nil UUID.
 #[pg_extern(immutable, parallel_safe)]
 fn timestamptz_to_uuid_v7_min(ts: pgrx::datum::TimestampWithTimeZone) -> pgrx::Uuid {
     let rv = [0x0 as u8; 10];
@@ -142,6 +815,8 @@ IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the
 /// Generate and return a new UUID using the v7 algorithm.
 /// The timestamp is the given timestamp.
 /// The UUID is the maximum UUID that can be generated for the given timestamp.
+/// At 10889-08-02 05:31:50.655 UTC, the end of the 48-bit timestamp range,
+/// it is ffffffff-ffff-7fff-bfff-ffffffffffff, which still sorts before the max UUID.
 #[pg_extern(immutable, parallel_safe)]
 fn timestamptz_to_uuid_v7_max(ts: pgrx::datum::TimestampWithTimeZone) -> pgrx::Uuid {
     let rv = [0xff as u8; 10];
@@ -157,6 +832,54 @@ IS 'Generate and return a new UUID using the v7 algorithm. The timestamp is the
     requires = [timestamptz_to_uuid_v7_max],
 );
 
+// Unlike the bounds above, the clamped bounds never raise an error,
+// so that comparisons and ranges with any timestamp, including ±infinity, are answered.
+#[inline]
+fn _timestamptz_to_uuid_v7_clamped(
+    ts: pgrx::datum::TimestampWithTimeZone,
+    rv: &[u8; 10],
+) -> pgrx::Uuid {
+    match cmp_uuid_v7_range(ts) {
+        std::cmp::Ordering::Less => Converter(Uuid::nil()).into(),
+        std::cmp::Ordering::Greater => Converter(Uuid::max()).into(),
+        std::cmp::Ordering::Equal => _timestamptz_to_uuid_v7(ts, rv),
+    }
+}
+
+/// Same as `timestamptz_to_uuid_v7_min`, but return the nil UUID for a timestamp before 1970
+/// or -infinity, and the max UUID for a timestamp after the range of UUIDv7 or +infinity.
+#[pg_extern(immutable, parallel_safe)]
+fn timestamptz_to_uuid_v7_min_clamped(ts: pgrx::datum::TimestampWithTimeZone) -> pgrx::Uuid {
+    let rv = [0x0_u8; 10];
+    _timestamptz_to_uuid_v7_clamped(ts, &rv)
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "timestamptz_to_uuid_v7_min_clamped"(timestamptz)
+IS 'Same as timestamptz_to_uuid_v7_min, but return the nil UUID for a timestamp before 1970 or -infinity, and the max UUID for a timestamp after the range of UUIDv7 or +infinity.';
+"#,
+    name = "comment_timestamptz_to_uuid_v7_min_clamped",
+    requires = [timestamptz_to_uuid_v7_min_clamped],
+);
+
+/// Same as `timestamptz_to_uuid_v7_max`, but return the nil UUID for a timestamp before 1970
+/// or -infinity, and the max UUID for a timestamp after the range of UUIDv7 or +infinity.
+#[pg_extern(immutable, parallel_safe)]
+fn timestamptz_to_uuid_v7_max_clamped(ts: pgrx::datum::TimestampWithTimeZone) -> pgrx::Uuid {
+    let rv = [0xff_u8; 10];
+    _timestamptz_to_uuid_v7_clamped(ts, &rv)
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "timestamptz_to_uuid_v7_max_clamped"(timestamptz)
+IS 'Same as timestamptz_to_uuid_v7_max, but return the nil UUID for a timestamp before 1970 or -infinity, and the max UUID for a timestamp after the range of UUIDv7 or +infinity.';
+"#,
+    name = "comment_timestamptz_to_uuid_v7_max_clamped",
+    requires = [timestamptz_to_uuid_v7_max_clamped],
+);
+
 extension_sql!(
     r#"
 -- uuid → bytea
@@ -239,12 +962,30 @@ IS 'PostgreSQL 18 compatible function. Generate and return a new UUID using the
     requires = [uuidv7_interval],
 );
 
+#[cfg(not(feature = "pg18"))]
+/// PostgreSQL 18 compatible alias for gen_random_uuid()
+/// Only available when targeting PostgreSQL < 18 to avoid conflicts
+#[pg_extern(parallel_safe)]
+fn uuidv4() -> pgrx::Uuid {
+    Converter(generator::new_v4()).into()
+}
+
+#[cfg(not(feature = "pg18"))]
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuidv4"()
+IS 'PostgreSQL 18 compatible alias for gen_random_uuid(). Generate and return a new UUID using the v4 algorithm.';
+"#,
+    name = "comment_uuidv4",
+    requires = [uuidv4],
+);
+
 #[cfg(not(feature = "pg18"))]
 /// PostgreSQL 18 compatible alias for uuid_get_version()
 /// Only available when targeting PostgreSQL < 18 to avoid conflicts
 #[pg_extern(parallel_safe)]
-fn uuid_extract_version(uuid: pgrx::Uuid) -> i16 {
-    uuid_get_version(uuid) as i16
+fn uuid_extract_version(uuid: pgrx::Uuid) -> Option<i16> {
+    uuid_get_version(uuid).map(|v| v as i16)
 }
 
 #[cfg(not(feature = "pg18"))]
@@ -263,43 +1004,59 @@ extension_sql!(
 CREATE DOMAIN uuidv1 AS uuid;
 
 ALTER DOMAIN uuidv1
-    ADD CONSTRAINT uuidv1 CHECK (uuid_extract_version(VALUE) = 1);
+    ADD CONSTRAINT uuidv1 CHECK (uuid_is_valid_v1(VALUE));
 
-COMMENT ON DOMAIN uuidv1 IS 'A UUID that is specifically version 1.';
+COMMENT ON DOMAIN uuidv1 IS 'A UUID that is specifically version 1, with the RFC 9562 variant.';
 
 -- UUIDv3
 CREATE DOMAIN uuidv3 AS uuid;
 
 ALTER DOMAIN uuidv3
-    ADD CONSTRAINT uuidv3 CHECK (uuid_extract_version(VALUE) = 3);
+    ADD CONSTRAINT uuidv3 CHECK (uuid_is_valid_v3(VALUE));
 
-COMMENT ON DOMAIN uuidv3 IS 'A UUID that is specifically version 3.';
+COMMENT ON DOMAIN uuidv3 IS 'A UUID that is specifically version 3, with the RFC 9562 variant.';
 
 -- UUIDv4
 CREATE DOMAIN uuidv4 AS uuid;
 
 ALTER DOMAIN uuidv4
-    ADD CONSTRAINT uuidv4 CHECK (uuid_extract_version(VALUE) = 4);
+    ADD CONSTRAINT uuidv4 CHECK (uuid_is_valid_v4(VALUE));
 
-COMMENT ON DOMAIN uuidv4 IS 'A UUID that is specifically version 4.';
+COMMENT ON DOMAIN uuidv4 IS 'A UUID that is specifically version 4, with the RFC 9562 variant.';
 
 -- UUIDv5
 CREATE DOMAIN uuidv5 AS uuid;
 
 ALTER DOMAIN uuidv5
-    ADD CONSTRAINT uuidv5 CHECK (uuid_extract_version(VALUE) = 5);
+    ADD CONSTRAINT uuidv5 CHECK (uuid_is_valid_v5(VALUE));
+
+COMMENT ON DOMAIN uuidv5 IS 'A UUID that is specifically version 5, with the RFC 9562 variant.';
+
+-- UUIDv6
+CREATE DOMAIN uuidv6 AS uuid;
+
+ALTER DOMAIN uuidv6
+    ADD CONSTRAINT uuidv6 CHECK (uuid_is_valid_v6(VALUE));
 
-COMMENT ON DOMAIN uuidv5 IS 'A UUID that is specifically version 5.';
+COMMENT ON DOMAIN uuidv6 IS 'A UUID that is specifically version 6, with the RFC 9562 variant.';
 
--- UUIDv7
-CREATE DOMAIN uuidv7 AS uuid;
+-- UUIDv8
+CREATE DOMAIN uuidv8 AS uuid;
 
-ALTER DOMAIN uuidv7 ADD CONSTRAINT uuidv7 CHECK (uuid_extract_version(VALUE) = 7);
+ALTER DOMAIN uuidv8
+    ADD CONSTRAINT uuidv8 CHECK (uuid_is_valid_v8(VALUE));
 
-COMMENT ON DOMAIN uuidv7 IS 'A UUID that is specifically version 7.';
+COMMENT ON DOMAIN uuidv8 IS 'A UUID that is specifically version 8, with the RFC 9562 variant.';
 "#,
     name = "domain_type_uuid_versions",
-    // requires = [uuid_extract_version],
+    requires = [
+        uuid_is_valid_v1,
+        uuid_is_valid_v3,
+        uuid_is_valid_v4,
+        uuid_is_valid_v5,
+        uuid_is_valid_v6,
+        uuid_is_valid_v8
+    ],
 );
 
 #[cfg(not(any(feature = "pg17", feature = "pg18")))]
@@ -329,6 +1086,175 @@ CREATE CAST (uuid AS timestamptz) WITH FUNCTION uuid_to_timestamptz(uuid) AS IMP
     requires = [uuid_to_timestamptz],
 );
 
+// Comparisons between uuid and timestamptz at the millisecond precision of UUIDv7.
+// The operator functions are inlined SQL functions, so that a comparison with a constant
+// becomes a comparison with a constant uuid, which can use any index on the uuid column.
+// They are not members of the btree uuid_ops family: a uuid "equals" every timestamp
+// in its millisecond, which is not transitive, so the family would be inconsistent.
+// A timestamp outside the range of UUIDv7 compares through the nil or the max UUID.
+extension_sql!(
+    r#"
+CREATE FUNCTION uuid_lt_timestamptz(u uuid, ts timestamptz) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u < timestamptz_to_uuid_v7_min_clamped(ts);
+
+CREATE FUNCTION uuid_le_timestamptz(u uuid, ts timestamptz) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u <= timestamptz_to_uuid_v7_max_clamped(ts);
+
+CREATE FUNCTION uuid_gt_timestamptz(u uuid, ts timestamptz) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u > timestamptz_to_uuid_v7_max_clamped(ts);
+
+CREATE FUNCTION uuid_ge_timestamptz(u uuid, ts timestamptz) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u >= timestamptz_to_uuid_v7_min_clamped(ts);
+
+CREATE FUNCTION timestamptz_lt_uuid(ts timestamptz, u uuid) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u > timestamptz_to_uuid_v7_max_clamped(ts);
+
+CREATE FUNCTION timestamptz_le_uuid(ts timestamptz, u uuid) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u >= timestamptz_to_uuid_v7_min_clamped(ts);
+
+CREATE FUNCTION timestamptz_gt_uuid(ts timestamptz, u uuid) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u < timestamptz_to_uuid_v7_min_clamped(ts);
+
+CREATE FUNCTION timestamptz_ge_uuid(ts timestamptz, u uuid) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u <= timestamptz_to_uuid_v7_max_clamped(ts);
+
+CREATE OPERATOR < (
+    LEFTARG = uuid, RIGHTARG = timestamptz, FUNCTION = uuid_lt_timestamptz,
+    COMMUTATOR = >, NEGATOR = >=,
+    RESTRICT = scalarltsel, JOIN = scalarltjoinsel
+);
+
+CREATE OPERATOR <= (
+    LEFTARG = uuid, RIGHTARG = timestamptz, FUNCTION = uuid_le_timestamptz,
+    COMMUTATOR = >=, NEGATOR = >,
+    RESTRICT = scalarlesel, JOIN = scalarlejoinsel
+);
+
+CREATE OPERATOR > (
+    LEFTARG = uuid, RIGHTARG = timestamptz, FUNCTION = uuid_gt_timestamptz,
+    COMMUTATOR = <, NEGATOR = <=,
+    RESTRICT = scalargtsel, JOIN = scalargtjoinsel
+);
+
+CREATE OPERATOR >= (
+    LEFTARG = uuid, RIGHTARG = timestamptz, FUNCTION = uuid_ge_timestamptz,
+    COMMUTATOR = <=, NEGATOR = <,
+    RESTRICT = scalargesel, JOIN = scalargejoinsel
+);
+
+CREATE OPERATOR < (
+    LEFTARG = timestamptz, RIGHTARG = uuid, FUNCTION = timestamptz_lt_uuid,
+    COMMUTATOR = >, NEGATOR = >=,
+    RESTRICT = scalarltsel, JOIN = scalarltjoinsel
+);
+
+CREATE OPERATOR <= (
+    LEFTARG = timestamptz, RIGHTARG = uuid, FUNCTION = timestamptz_le_uuid,
+    COMMUTATOR = >=, NEGATOR = >,
+    RESTRICT = scalarlesel, JOIN = scalarlejoinsel
+);
+
+CREATE OPERATOR > (
+    LEFTARG = timestamptz, RIGHTARG = uuid, FUNCTION = timestamptz_gt_uuid,
+    COMMUTATOR = <, NEGATOR = <=,
+    RESTRICT = scalargtsel, JOIN = scalargtjoinsel
+);
+
+CREATE OPERATOR >= (
+    LEFTARG = timestamptz, RIGHTARG = uuid, FUNCTION = timestamptz_ge_uuid,
+    COMMUTATOR = <=, NEGATOR = <,
+    RESTRICT = scalargesel, JOIN = scalargejoinsel
+);
+
+CREATE FUNCTION uuid_contained_by_tstzrange(u uuid, r tstzrange) RETURNS bool
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    RETURN NOT isempty(r)
+        AND CASE
+            WHEN lower_inf(r) THEN u >= '00000000-0000-0000-0000-000000000000'::uuid
+            WHEN lower_inc(r) THEN u >= timestamptz_to_uuid_v7_min_clamped(lower(r))
+            ELSE u > timestamptz_to_uuid_v7_max_clamped(lower(r))
+        END
+        AND CASE
+            WHEN upper_inf(r) THEN u <= 'ffffffff-ffff-ffff-ffff-ffffffffffff'::uuid
+            WHEN upper_inc(r) THEN u <= timestamptz_to_uuid_v7_max_clamped(upper(r))
+            ELSE u < timestamptz_to_uuid_v7_min_clamped(upper(r))
+        END;
+
+CREATE FUNCTION tstzrange_contains_uuid(r tstzrange, u uuid) RETURNS bool
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    RETURN uuid_contained_by_tstzrange(u, r);
+
+CREATE OPERATOR <@ (
+    LEFTARG = uuid, RIGHTARG = tstzrange, FUNCTION = uuid_contained_by_tstzrange,
+    COMMUTATOR = @>,
+    RESTRICT = contsel, JOIN = contjoinsel
+);
+
+CREATE OPERATOR @> (
+    LEFTARG = tstzrange, RIGHTARG = uuid, FUNCTION = tstzrange_contains_uuid,
+    COMMUTATOR = <@,
+    RESTRICT = contsel, JOIN = contjoinsel
+);
+
+COMMENT ON OPERATOR < (uuid, timestamptz)
+IS 'The timestamp encoded in the UUID is before the millisecond of the given timestamp.';
+COMMENT ON OPERATOR <= (uuid, timestamptz)
+IS 'The timestamp encoded in the UUID is before or in the millisecond of the given timestamp.';
+COMMENT ON OPERATOR > (uuid, timestamptz)
+IS 'The timestamp encoded in the UUID is after the millisecond of the given timestamp.';
+COMMENT ON OPERATOR >= (uuid, timestamptz)
+IS 'The timestamp encoded in the UUID is in or after the millisecond of the given timestamp.';
+COMMENT ON OPERATOR <@ (uuid, tstzrange)
+IS 'The timestamp encoded in the UUID is in the given range, at millisecond precision.';
+"#,
+    name = "uuid_timestamptz_operators",
+    requires = [
+        timestamptz_to_uuid_v7_min_clamped,
+        timestamptz_to_uuid_v7_max_clamped
+    ],
+);
+
+// The inverse of uuid_to_tstzrange: the UUIDv7 bounds of a tstzrange, inclusive on both ends.
+// A UUID is between them exactly when it is `<@` the range.
+// Bounds outside the range of UUIDv7, such as ±infinity, become the nil or the max UUID.
+// A range that holds no whole millisecond gives lower > upper, so that no UUID is between them.
+extension_sql!(
+    r#"
+CREATE FUNCTION tstzrange_to_uuid_range(r tstzrange, OUT lower uuid, OUT upper uuid)
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$
+    SELECT
+        CASE
+            WHEN lower_inf(r) THEN '00000000-0000-0000-0000-000000000000'::uuid
+            WHEN lower_inc(r) THEN timestamptz_to_uuid_v7_min_clamped(lower(r))
+            ELSE timestamptz_to_uuid_v7_min_clamped(lower(r) + INTERVAL '1 millisecond')
+        END,
+        CASE
+            WHEN upper_inf(r) THEN 'ffffffff-ffff-ffff-ffff-ffffffffffff'::uuid
+            WHEN upper_inc(r) THEN timestamptz_to_uuid_v7_max_clamped(upper(r))
+            ELSE timestamptz_to_uuid_v7_max_clamped(upper(r) - INTERVAL '1 millisecond')
+        END
+    WHERE NOT isempty(r)
+    $$;
+
+COMMENT ON FUNCTION "tstzrange_to_uuid_range"(tstzrange)
+IS 'Return the lowest and highest UUIDv7 whose timestamps are in the given range, at millisecond precision. A bound before 1970 or at -infinity becomes the nil UUID, and a bound after the range of UUIDv7 or at infinity becomes the max UUID. For a range that holds no whole millisecond, lower is greater than upper, so that no UUID is between them. Return NULL for an empty range.';
+"#,
+    name = "tstzrange_to_uuid_range",
+    requires = [
+        timestamptz_to_uuid_v7_min_clamped,
+        timestamptz_to_uuid_v7_max_clamped
+    ],
+);
+
 #[cfg(any(test, feature = "pg_test"))]
 mod tests;
 
@@ -1,4 +1,5 @@
-use chrono::{DateTime, Datelike, Timelike, Utc};
+use crate::generator::SUBMS_BITS;
+use crate::guc::{TimestampOutOfRange, TIMESTAMP_OUT_OF_RANGE};
 use pgrx::prelude::*;
 
 #[derive(Debug)]
@@ -25,71 +26,179 @@ impl From<Converter<pgrx::Uuid>> for uuid::Uuid {
     }
 }
 
-impl From<Converter<pgrx::datum::TimestampWithTimeZone>> for uuid::Timestamp {
+impl From<Converter<uuid::Timestamp>> for pgrx::datum::TimestampWithTimeZone {
     #[inline]
-    fn from(w: Converter<pgrx::datum::TimestampWithTimeZone>) -> Self {
-        let ts = w.unwrap().to_utc();
-        let epoch: u64 = ts
-            .extract_part(DateTimeParts::Epoch)
-            .unwrap()
-            .try_into()
-            .unwrap();
-        let nanoseconds: u32 = (ts.second().fract() * 1_000_000_000.0) as u32;
-        uuid::Timestamp::from_unix(uuid::timestamp::context::NoContext, epoch, nanoseconds)
+    fn from(w: Converter<uuid::Timestamp>) -> Self {
+        // Using pgrx::datum::Timestamp::from does not work well.
+        // because Postgres Epoch is from 2000-01-01 00:00:00 UTC.
+        // refer: https://docs.rs/pgrx/0.10.2/pgrx/datum/struct.Timestamp.html#impl-From%3Ci64%3E-for-Timestamp
+        let (seconds, nanoseconds) = w.unwrap().to_unix();
+        // rounded to microseconds
+        from_unix_micros(seconds as i128 * 1_000_000 + (nanoseconds as i128 + 500) / 1_000)
     }
 }
 
-impl From<Converter<uuid::Timestamp>> for chrono::DateTime<Utc> {
-    #[inline]
-    fn from(value: Converter<uuid::Timestamp>) -> Self {
-        let ts = value.unwrap();
-        let (epoch, nanoseconds) = ts.to_unix();
-        DateTime::from_timestamp(epoch as i64, nanoseconds % 1_000_000_000).unwrap()
+// Microseconds from the unix epoch to the Postgres epoch, 2000-01-01 00:00:00 UTC.
+const POSTGRES_EPOCH_UNIX_MICROS: i128 = 946_684_800_000_000;
+// Microseconds from the Gregorian epoch, 1582-10-15 00:00:00 UTC, to the unix epoch.
+const GREGORIAN_EPOCH_UNIX_MICROS: i128 = -12_219_292_800_000_000;
+
+/// The largest unix timestamp in milliseconds that fits in the 48 bits of UUIDv7.
+pub const UUID_V7_MAX_MILLIS: u64 = (1 << 48) - 1;
+// The largest unix timestamp in microseconds, in the last millisecond of UUIDv7.
+const UUID_V7_MAX_MICROS: u64 = UUID_V7_MAX_MILLIS * 1_000 + 999;
+/// The largest timestamp in 100-nanosecond ticks that fits in the 60 bits of UUIDv1 and UUIDv6.
+pub const UUID_V1_MAX_TICKS: u64 = (1 << 60) - 1;
+
+/// Return the microseconds since the unix epoch, or None for an infinite timestamp.
+fn to_unix_micros(ts: pgrx::datum::TimestampWithTimeZone) -> Option<i128> {
+    match pg_sys::TimestampTz::from(ts) {
+        i64::MIN | i64::MAX => None,
+        micros => Some(micros as i128 + POSTGRES_EPOCH_UNIX_MICROS),
     }
 }
 
-impl From<Converter<chrono::DateTime<Utc>>> for pgrx::datum::TimestampWithTimeZone {
-    #[inline]
-    fn from(w: Converter<chrono::DateTime<Utc>>) -> Self {
-        let dt = w.unwrap();
-        pgrx::datum::TimestampWithTimeZone::with_timezone(
-            dt.year() as i32,
-            dt.month() as u8,
-            dt.day() as u8,
-            dt.hour() as u8,
-            dt.minute() as u8,
-            dt.second() as f64 + dt.nanosecond() as f64 / 1_000_000_000.0,
-            "UTC",
-        )
-        .unwrap()
+fn report_out_of_range(ts: pgrx::datum::TimestampWithTimeZone, version: &str, range: &str) {
+    let ts = ts
+        .to_iso_string_with_timezone("UTC")
+        .unwrap_or_else(|_| format!("{ts:?}"));
+    ereport!(
+        ERROR,
+        PgSqlErrorCode::ERRCODE_DATETIME_FIELD_OVERFLOW,
+        format!("timestamp out of range for {version}: \"{ts}\""),
+        format!("{version} can represent timestamps from {range}.")
+    );
+}
+
+/// Compare the timestamp with the range of UUIDv7:
+/// `Less` before 1970-01-01 00:00:00 UTC or at -infinity,
+/// `Greater` after the last millisecond of the 48-bit range or at +infinity, and `Equal` within it.
+pub fn cmp_uuid_v7_range(ts: pgrx::datum::TimestampWithTimeZone) -> std::cmp::Ordering {
+    match to_unix_micros(ts) {
+        Some(micros) if micros < 0 => std::cmp::Ordering::Less,
+        Some(micros) if micros > UUID_V7_MAX_MICROS as i128 => std::cmp::Ordering::Greater,
+        Some(_) => std::cmp::Ordering::Equal,
+        None if ts.is_neg_infinity() => std::cmp::Ordering::Less,
+        None => std::cmp::Ordering::Greater,
     }
 }
 
-impl From<Converter<uuid::Timestamp>> for pgrx::datum::TimestampWithTimeZone {
-    #[inline]
-    fn from(w: Converter<uuid::Timestamp>) -> Self {
-        // Using pgrx::datum::Timestamp::from does not work well.
-        // because Postgres Epoch is from 2000-01-01 00:00:00 UTC.
-        // refer: https://docs.rs/pgrx/0.10.2/pgrx/datum/struct.Timestamp.html#impl-From%3Ci64%3E-for-Timestamp
-        let ts = w.unwrap();
-        let datetime: DateTime<Utc> = Converter(ts).into();
-        Converter(datetime).into()
+/// Return the unix microseconds of the timestamp for UUIDv7,
+/// handling a timestamp outside the 48-bit millisecond range according to `policy`.
+pub fn to_uuid_timestamp_micros_with(
+    ts: pgrx::datum::TimestampWithTimeZone,
+    policy: TimestampOutOfRange,
+) -> u64 {
+    let micros = to_unix_micros(ts);
+    match micros {
+        Some(micros) if (0..=UUID_V7_MAX_MICROS as i128).contains(&micros) => micros as u64,
+        _ => match (policy, micros) {
+            (TimestampOutOfRange::Clamp, Some(micros)) if micros < 0 => 0,
+            (TimestampOutOfRange::Clamp, Some(_)) => UUID_V7_MAX_MICROS,
+            (TimestampOutOfRange::Clamp, None) if ts.is_neg_infinity() => 0,
+            (TimestampOutOfRange::Clamp, None) => UUID_V7_MAX_MICROS,
+            (TimestampOutOfRange::Wrap, Some(micros)) => {
+                micros.rem_euclid(UUID_V7_MAX_MICROS as i128 + 1) as u64
+            }
+            _ => {
+                report_out_of_range(
+                    ts,
+                    "UUIDv7",
+                    "1970-01-01 00:00:00 UTC to 10889-08-02 05:31:50.655 UTC",
+                );
+                0
+            }
+        },
     }
 }
 
+/// Return the unix microseconds of the timestamp for UUIDv7,
+/// handling a timestamp outside the 48-bit millisecond range according to `pgx_uuidv7.timestamp_out_of_range`.
+#[inline]
+pub fn to_uuid_timestamp_micros(ts: pgrx::datum::TimestampWithTimeZone) -> u64 {
+    to_uuid_timestamp_micros_with(ts, TIMESTAMP_OUT_OF_RANGE.get())
+}
+
+/// Return the unix milliseconds of the timestamp for UUIDv7,
+/// handling a timestamp outside the 48-bit range according to `policy`.
+#[inline]
+pub fn to_uuid_timestamp_buildpart_with(
+    ts: pgrx::datum::TimestampWithTimeZone,
+    policy: TimestampOutOfRange,
+) -> u64 {
+    to_uuid_timestamp_micros_with(ts, policy) / 1_000
+}
+
+/// Return the unix milliseconds of the timestamp for UUIDv7,
+/// handling a timestamp outside the 48-bit range according to `pgx_uuidv7.timestamp_out_of_range`.
 #[inline]
 pub fn to_uuid_timestamp_buildpart(ts: pgrx::datum::TimestampWithTimeZone) -> u64 {
-    let ut: uuid::Timestamp = Converter(ts).into();
-    let (secs, nanos) = ut.to_unix();
-    let millis = (secs * 1000).saturating_add(nanos as u64 / 1_000_000);
-    millis
+    to_uuid_timestamp_buildpart_with(ts, TIMESTAMP_OUT_OF_RANGE.get())
+}
+
+/// Encode the microseconds within the millisecond in the 12 bits of `rand_a`,
+/// as `ceil(us * 4096 / 1000)` so that `decode_submillis_micros` gives them back exactly.
+#[inline]
+pub fn encode_submillis_micros(micros: u64) -> u16 {
+    ((micros % 1_000) << SUBMS_BITS).div_ceil(1_000) as u16
+}
+
+/// Decode the microseconds within the millisecond from the 12 bits of `rand_a`.
+#[inline]
+pub fn decode_submillis_micros(rand_a: u16) -> u64 {
+    ((rand_a as u64 & ((1 << SUBMS_BITS) - 1)) * 1_000) >> SUBMS_BITS
+}
+
+/// Return the unix microseconds of the timestamp of a UUIDv1, UUIDv6 or UUIDv7,
+/// or None for the other versions.
+/// The fields are read directly, so that UUIDv1 and UUIDv6 before 1970 are not wrapped.
+/// Their 100-nanosecond ticks are truncated to microseconds, like `uuid_extract_timestamp` of Postgres 17.
+#[inline]
+pub fn uuid_timestamp_unix_micros(u: &uuid::Uuid) -> Option<i128> {
+    let v = u.as_u128();
+    match u.get_version_num() {
+        1 => {
+            let ticks = ((v >> 64) & 0x0fff) << 48 | ((v >> 80) & 0xffff) << 32 | (v >> 96);
+            Some(ticks as i128 / 10 + GREGORIAN_EPOCH_UNIX_MICROS)
+        }
+        6 => {
+            let ticks = (v >> 80) << 12 | ((v >> 64) & 0x0fff);
+            Some(ticks as i128 / 10 + GREGORIAN_EPOCH_UNIX_MICROS)
+        }
+        7 => Some((v >> 80) as i128 * 1_000),
+        _ => None,
+    }
+}
+
+/// Return the timestamptz for the given unix microseconds.
+#[inline]
+pub fn from_unix_micros(micros: i128) -> pgrx::datum::TimestampWithTimeZone {
+    let pg_micros = (micros - POSTGRES_EPOCH_UNIX_MICROS) as pg_sys::TimestampTz;
+    pgrx::datum::TimestampWithTimeZone::try_from(pg_micros).unwrap()
+}
+
+/// Return the 100-nanosecond ticks since the Gregorian epoch of the timestamp for UUIDv1 and UUIDv6.
+/// Raise an error for a timestamp outside the 60-bit range.
+#[inline]
+pub fn to_uuid_gregorian_ticks(ts: pgrx::datum::TimestampWithTimeZone) -> u64 {
+    let ticks = to_unix_micros(ts).map(|micros| (micros - GREGORIAN_EPOCH_UNIX_MICROS) * 10);
+    match ticks {
+        Some(ticks) if (0..=UUID_V1_MAX_TICKS as i128).contains(&ticks) => ticks as u64,
+        _ => {
+            report_out_of_range(
+                ts,
+                "UUIDv1 and UUIDv6",
+                "1582-10-15 00:00:00 UTC to 5236-03-31 21:21:00.684697 UTC",
+            );
+            0
+        }
+    }
 }
 
 #[cfg(any(test, feature = "pg_test"))]
 #[pg_schema]
 mod tests {
     use super::*;
-    use chrono::prelude::*;
 
     #[pg_test]
     fn uuid_all() {
@@ -102,14 +211,6 @@ mod tests {
     }
 
     // generage same timestamp "2012-03-04T05:06:07.123456789Z"
-    fn gen_ct() -> DateTime<Utc> {
-        NaiveDate::from_ymd_opt(2012, 3, 4)
-            .unwrap()
-            .and_hms_nano_opt(5, 6, 7, 123_456_789)
-            .unwrap()
-            .and_local_timezone(Utc)
-            .unwrap()
-    }
     fn gen_ut() -> uuid::Timestamp {
         uuid::Timestamp::from_unix(
             uuid::timestamp::context::NoContext,
@@ -122,17 +223,28 @@ mod tests {
             .unwrap()
     }
 
-    #[pg_test]
-    fn timestamp_reference() {
-        // check chrono::DateTime
-        let ct = gen_ct();
+    // check chrono::DateTime, only in `cargo test`, since chrono is a dev-dependency
+    // and this module is also built into the extension with the pg_test feature.
+    #[cfg(test)]
+    #[test]
+    fn timestamp_reference_chrono() {
+        use chrono::prelude::*;
+        let ct = NaiveDate::from_ymd_opt(2012, 3, 4)
+            .unwrap()
+            .and_hms_nano_opt(5, 6, 7, 123_456_789)
+            .unwrap()
+            .and_local_timezone(Utc)
+            .unwrap();
         assert_eq!(
             ct.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
             "2012-03-04T05:06:07.123456789Z"
         );
         assert_eq!(ct.timestamp(), 1_330_837_567);
         assert_eq!(ct.timestamp_subsec_nanos(), 123_456_789);
+    }
 
+    #[pg_test]
+    fn timestamp_reference() {
         // check uuid::timestamp
         let ut = gen_ut();
         let (epoch, nanoseconds) = ut.to_unix();
@@ -162,10 +274,38 @@ mod tests {
     #[pg_test]
     fn timestamp_pt_to_ut() {
         let pt = gen_pt();
-        let ut_out: uuid::Timestamp = Converter(pt).into();
-        let (epoch, nanoseconds) = ut_out.to_unix();
-        assert_eq!(epoch, 1_330_837_567);
+        let micros = to_uuid_timestamp_micros_with(pt, TimestampOutOfRange::Error);
         // rounded up to microseconds
-        assert_eq!(nanoseconds, 123_457_000);
+        assert_eq!(micros, 1_330_837_567_123_457);
+    }
+
+    #[pg_test]
+    fn submillis_micros_round_trip() {
+        for micros in 0..1_000 {
+            let rand_a = encode_submillis_micros(1_330_837_567_123_000 + micros);
+            assert!(rand_a < 1 << SUBMS_BITS);
+            assert_eq!(decode_submillis_micros(rand_a), micros);
+        }
+        let micros = to_uuid_timestamp_micros(gen_pt());
+        assert_eq!(micros, 1_330_837_567_123_457);
+        let pt_out = from_unix_micros(micros as i128);
+        assert_eq!(
+            pt_out.to_iso_string_with_timezone("UTC").unwrap(),
+            "2012-03-04T05:06:07.123457+00:00"
+        );
+    }
+
+    #[pg_test]
+    fn timestamp_pt_gregorian_round_trip() {
+        let pt = gen_pt();
+        let ticks = to_uuid_gregorian_ticks(pt);
+        // 100-nanosecond ticks from 1582-10-15 to 2012-03-04T05:06:07.123457
+        assert_eq!(ticks, 135_501_303_671_234_570);
+        let ut = uuid::Timestamp::from_gregorian_time(ticks, 0);
+        let pt_out: pgrx::datum::TimestampWithTimeZone = Converter(ut).into();
+        assert_eq!(
+            pt_out.to_iso_string_with_timezone("UTC").unwrap(),
+            "2012-03-04T05:06:07.123457+00:00"
+        );
     }
 }
@@ -12,7 +12,71 @@ mod tests {
         assert_eq!(7, u.get_version_num());
 
         let v = uuid_get_version(g);
-        assert_eq!(7, v);
+        assert_eq!(Some(7), v);
+    }
+
+    #[pg_test]
+    fn test_pgx_uuidv7_monotonic() {
+        let mut prev = uuid_generate_v7_monotonic();
+        for _ in 0..10_000 {
+            let g = uuid_generate_v7_monotonic();
+            let u: uuid::Uuid = Converter(g).into();
+            assert_eq!(7, u.get_version_num());
+            assert_eq!(Variant::RFC4122, u.get_variant());
+            assert!(prev < g, "UUIDs from the same backend must be increasing");
+            prev = g;
+        }
+    }
+
+    #[pg_test]
+    fn test_pgx_uuidv7_monotonic_global() {
+        let mut prev = uuid_generate_v7_monotonic_global();
+        for _ in 0..10_000 {
+            let g = uuid_generate_v7_monotonic_global();
+            let u: uuid::Uuid = Converter(g).into();
+            assert_eq!(7, u.get_version_num());
+            assert_eq!(Variant::RFC4122, u.get_variant());
+            assert!(prev < g, "UUIDs from the cluster must be increasing");
+            prev = g;
+        }
+
+        let in_order = Spi::get_one::<bool>(
+            "
+            WITH ids AS (
+                SELECT n, uuid_generate_v7_monotonic_global() AS id
+                FROM generate_series(1, 1000) AS n
+            )
+            SELECT bool_and(id > prev_id) FROM (
+                SELECT id, lag(id) OVER (ORDER BY n) AS prev_id FROM ids
+            ) t
+            WHERE prev_id IS NOT NULL;
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert!(in_order);
+    }
+
+    #[pg_test]
+    fn test_monotonic_clock_goes_backwards() {
+        // the clock moves forward
+        assert_eq!(generator::advance_timestamp(100, 200), 200);
+        // the clock stalls
+        assert_eq!(generator::advance_timestamp(200, 200), 201);
+        // the clock goes backwards
+        assert_eq!(generator::advance_timestamp(200, 100), 201);
+
+        // the sub-millisecond fraction is stored in rand_a
+        let ts = (1_330_837_567_123 << generator::SUBMS_BITS) | 0xabc;
+        let u = generator::from_timestamp_with_submillis(ts);
+        assert_eq!(7, u.get_version_num());
+        assert_eq!(
+            0xabc,
+            u16::from_be_bytes([u.as_bytes()[6], u.as_bytes()[7]]) & 0x0fff
+        );
+        let (epoch, nanoseconds) = u.get_timestamp().unwrap().to_unix();
+        assert_eq!(epoch, 1_330_837_567);
+        assert_eq!(nanoseconds, 123_000_000);
     }
 
     fn gen_pt() -> pgrx::datum::TimestampWithTimeZone {
@@ -41,6 +105,435 @@ mod tests {
         assert_eq!(pt001, pt002);
     }
 
+    #[pg_test]
+    fn test_pgx_uuidv7_counter() {
+        let pt = gen_pt();
+        let mut prev = uuid_generate_v7_counter(pt);
+        for _ in 0..10_000 {
+            let g = uuid_generate_v7_counter(pt);
+            let u: uuid::Uuid = Converter(g).into();
+            assert_eq!(7, u.get_version_num());
+            assert_eq!(Variant::RFC4122, u.get_variant());
+            assert!(
+                prev < g,
+                "UUIDs for the same millisecond must be increasing"
+            );
+            prev = g;
+        }
+        assert_eq!(
+            uuid_to_timestamptz(prev).unwrap(),
+            pgrx::datum::TimestampWithTimeZone::with_timezone(2012, 3, 4, 5, 6, 7.123, "UTC")
+                .unwrap()
+        );
+
+        let in_order = Spi::get_one::<bool>(
+            "
+            WITH ids AS (
+                SELECT n, uuid_generate_v7_counter('2012-03-04T05:06:07.123456789+00:00') AS id
+                FROM generate_series(1, 1000) AS n
+            )
+            SELECT bool_and(id > prev_id) FROM (
+                SELECT id, lag(id) OVER (ORDER BY n) AS prev_id FROM ids
+            ) t
+            WHERE prev_id IS NOT NULL;
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert!(in_order);
+    }
+
+    #[pg_test]
+    fn test_counter_overflow() {
+        let counter = generator::CounterV7::new();
+        // a new millisecond seeds the counter
+        assert_eq!(counter.advance(1_000, 5), (1_000, 5));
+        // the same millisecond increments it
+        assert_eq!(counter.advance(1_000, 9), (1_000, 6));
+        // a different millisecond reseeds it, even an earlier one
+        assert_eq!(counter.advance(999, 7), (999, 7));
+        // an overflow borrows one millisecond from the timestamp
+        assert_eq!(
+            counter.advance(2_000, generator::COUNTER_MAX),
+            (2_000, generator::COUNTER_MAX)
+        );
+        assert_eq!(counter.advance(2_000, 3), (2_001, 3));
+        assert_eq!(counter.advance(2_000, 8), (2_001, 4));
+
+        // the counter is stored in rand_a and the top 30 bits of rand_b
+        let u = generator::from_millis_and_counter(2_001, 0x2aa_aaaa_aaaa);
+        assert_eq!(7, u.get_version_num());
+        assert_eq!(Variant::RFC4122, u.get_variant());
+        let v = u.as_u128();
+        let rand_a = (v >> 64) & 0xfff;
+        let rand_b_top = (v >> 32) & 0x3fff_ffff;
+        assert_eq!((rand_a << 30) | rand_b_top, 0x2aa_aaaa_aaaa);
+        // carrying from rand_b into rand_a keeps the order
+        assert!(
+            generator::from_millis_and_counter(2_001, 0x3fff_ffff)
+                < generator::from_millis_and_counter(2_001, 0x4000_0000)
+        );
+    }
+
+    #[pg_test]
+    fn test_pgx_uuidv7_batch() {
+        let pt = gen_pt();
+        let batch = uuid_generate_v7_batch(1000, pt);
+        assert_eq!(batch.len(), 1000);
+        let batch: Vec<uuid::Uuid> = batch.into_iter().map(|u| Converter(u).into()).collect();
+        for u in &batch {
+            assert_eq!(7, u.get_version_num());
+            assert_eq!(Variant::RFC4122, u.get_variant());
+        }
+        for w in batch.windows(2) {
+            assert!(w[0] < w[1]);
+        }
+        // a following batch for the same millisecond continues the counter
+        let next: uuid::Uuid = Converter(uuid_generate_v7_batch(1, pt)[0]).into();
+        assert!(batch[999] < next);
+
+        let (len, ordered) = Spi::get_two::<i32, bool>(
+            "
+            SELECT cardinality(b), b = ARRAY(SELECT unnest(b) ORDER BY 1)
+            FROM (SELECT uuid_generate_v7_batch(10) AS b) t;
+            ",
+        )
+        .unwrap();
+        assert_eq!(len, Some(10));
+        assert_eq!(ordered, Some(true));
+        assert!(uuid_generate_v7_batch(0, pt).is_empty());
+        assert_eq!(
+            error_code("SELECT uuid_generate_v7_batch(67108864)"),
+            Some(PgSqlErrorCode::ERRCODE_PROGRAM_LIMIT_EXCEEDED)
+        );
+    }
+
+    #[pg_test]
+    fn test_stateful_generators_parallel_restricted() {
+        for function in [
+            "uuid_generate_v1_now",
+            "uuid_generate_v1",
+            "uuid_generate_v6_now",
+            "uuid_generate_v6",
+        ] {
+            let parallel = Spi::get_one::<String>(&format!(
+                "SELECT proparallel::text FROM pg_proc WHERE proname = '{function}'"
+            ))
+            .unwrap();
+            assert_eq!(parallel.as_deref(), Some("r"), "{function}");
+        }
+    }
+
+    #[pg_test]
+    fn test_pgx_uuidv4_batch() {
+        let batch = uuid_generate_v4_batch(100);
+        assert_eq!(batch.len(), 100);
+        let batch: std::collections::HashSet<uuid::Uuid> =
+            batch.into_iter().map(|u| Converter(u).into()).collect();
+        assert_eq!(batch.len(), 100);
+        for u in &batch {
+            assert_eq!(4, u.get_version_num());
+            assert_eq!(Variant::RFC4122, u.get_variant());
+        }
+        assert!(uuid_generate_v4_batch(0).is_empty());
+        assert_eq!(
+            error_code("SELECT uuid_generate_v4_batch(67108864)"),
+            Some(PgSqlErrorCode::ERRCODE_PROGRAM_LIMIT_EXCEEDED)
+        );
+
+        // uuidv4() is the same SQL on every supported PostgreSQL version
+        let version = Spi::get_one::<i16>("SELECT uuid_extract_version(uuidv4());").unwrap();
+        assert_eq!(version, Some(4));
+    }
+
+    #[pg_test]
+    fn test_pgx_uuidv7_series() {
+        let (count, ordered, distinct_ms) = Spi::get_three::<i64, bool, i64>(
+            "
+            SELECT count(*), bool_and(ordered), count(DISTINCT uuid_to_timestamptz(u))
+            FROM (
+                SELECT u, u > lag(u) OVER () IS NOT FALSE AS ordered
+                FROM uuid_generate_v7_series(
+                    '2012-03-04T05:06:07+00:00',
+                    '2012-03-04T05:06:08+00:00',
+                    '100 microseconds',
+                    3
+                ) AS u
+            ) t;
+            ",
+        )
+        .unwrap();
+        // 10001 steps of 3 UUIDs, in 1001 distinct milliseconds
+        assert_eq!(count, Some(30003));
+        assert_eq!(ordered, Some(true));
+        assert_eq!(distinct_ms, Some(1001));
+
+        let (count, bounds) = Spi::get_two::<i64, bool>(
+            "
+            SELECT
+                count(*),
+                max(uuid_to_timestamptz(u)) = '2012-03-05T00:00:00+00:00'
+                    AND min(uuid_to_timestamptz(u)) = '2012-03-04T00:00:00+00:00'
+            FROM uuid_generate_v7_series(
+                '2012-03-05T00:00:00+00:00',
+                '2012-03-04T00:00:00+00:00',
+                '-1 hour'
+            ) AS u;
+            ",
+        )
+        .unwrap();
+        assert_eq!(count, Some(25));
+        assert_eq!(bounds, Some(true));
+
+        let result = PgTryBuilder::new(|| {
+            Spi::run("SELECT uuid_generate_v7_series(now(), now(), '0 seconds')").unwrap();
+            false
+        })
+        .catch_others(|_| true)
+        .execute();
+        assert!(result, "a zero step should raise an error");
+    }
+
+    #[pg_test]
+    fn test_pgx_uuidv1() {
+        let u: uuid::Uuid = Converter(uuid_generate_v1_now()).into();
+        assert_eq!(1, u.get_version_num());
+        assert_eq!(Variant::RFC4122, u.get_variant());
+        // the random node ID has the multicast bit set
+        assert_eq!(u.get_node_id().unwrap()[0] & 0x01, 0x01);
+
+        let pt = gen_pt();
+        let u: uuid::Uuid =
+            Converter(uuid_generate_v1(Some(pt), Some(&[1, 2, 3, 4, 5, 6])).unwrap()).into();
+        assert_eq!(1, u.get_version_num());
+        assert_eq!(u.get_node_id(), Some([1, 2, 3, 4, 5, 6]));
+
+        // the timestamp round-trips at the microsecond precision of timestamptz
+        let pt_out = uuid_to_timestamptz(Converter(u).into()).unwrap();
+        assert_eq!(
+            pt_out.to_iso_string_with_timezone("UTC").unwrap(),
+            "2012-03-04T05:06:07.123457+00:00"
+        );
+
+        // the clock sequence makes UUIDs for the same timestamp distinct
+        let u2: uuid::Uuid =
+            Converter(uuid_generate_v1(Some(pt), Some(&[1, 2, 3, 4, 5, 6])).unwrap()).into();
+        assert_ne!(u, u2);
+        assert_eq!(u.get_timestamp(), u2.get_timestamp());
+
+        // test vector from RFC 9562 appendix A.1
+        let rfc = Spi::get_one::<bool>(
+            "
+            SELECT uuid_to_timestamptz('c232ab00-9414-11ec-b3c8-9f6bdeced846')
+                = '2022-02-22T19:22:22+00:00';
+            ",
+        )
+        .unwrap();
+        assert_eq!(rfc, Some(true));
+
+        let result = PgTryBuilder::new(|| {
+            uuid_generate_v1(Some(pt), Some(&[1, 2, 3]));
+            false
+        })
+        .catch_others(|_| true)
+        .execute();
+        assert!(
+            result,
+            "a node that is not 6 bytes long should raise an error"
+        );
+    }
+
+    #[pg_test]
+    fn test_pgx_uuidv6() {
+        let u: uuid::Uuid = Converter(uuid_generate_v6_now()).into();
+        assert_eq!(6, u.get_version_num());
+        assert_eq!(Variant::RFC4122, u.get_variant());
+
+        let pt = gen_pt();
+        let u: uuid::Uuid =
+            Converter(uuid_generate_v6(Some(pt), Some(&[1, 2, 3, 4, 5, 6])).unwrap()).into();
+        assert_eq!(6, u.get_version_num());
+        assert_eq!(u.get_node_id(), Some([1, 2, 3, 4, 5, 6]));
+        let pt_out = uuid_to_timestamptz(Converter(u).into()).unwrap();
+        assert_eq!(
+            pt_out.to_iso_string_with_timezone("UTC").unwrap(),
+            "2012-03-04T05:06:07.123457+00:00"
+        );
+
+        // UUIDv6 sorts by time
+        let ordered = Spi::get_one::<bool>(
+            "
+            SELECT bool_and(ordered IS NOT FALSE)
+            FROM (
+                SELECT u > lag(u) OVER (ORDER BY n) AS ordered
+                FROM (
+                    SELECT n, uuid_generate_v6(
+                        '2012-03-04T05:06:07+00:00'::timestamptz + n * INTERVAL '1 microsecond'
+                    ) AS u
+                    FROM generate_series(1, 1000) AS n
+                ) t
+            ) t;
+            ",
+        )
+        .unwrap();
+        assert_eq!(ordered, Some(true));
+    }
+
+    #[pg_test]
+    fn test_uuid_v1_v6_conversion() {
+        // test vectors from RFC 9562 appendix A.1 and A.4, with the same time, clock sequence and node
+        let v1 =
+            pgrx::Uuid::from_bytes(*uuid::uuid!("c232ab00-9414-11ec-b3c8-9f6bdeced846").as_bytes());
+        let v6 =
+            pgrx::Uuid::from_bytes(*uuid::uuid!("1ec9414c-232a-6b00-b3c8-9f6bdeced846").as_bytes());
+        assert_eq!(uuid_v1_to_v6(v1), v6);
+        assert_eq!(uuid_v6_to_v1(v6), v1);
+        assert_eq!(uuid_to_timestamptz(v1), uuid_to_timestamptz(v6));
+
+        // lossless for generated UUIDs
+        let pt = gen_pt();
+        for _ in 0..100 {
+            let u = uuid_generate_v1(Some(pt), None).unwrap();
+            assert_eq!(uuid_v6_to_v1(uuid_v1_to_v6(u)), u);
+        }
+
+        let result = PgTryBuilder::new(|| {
+            uuid_v1_to_v6(v6);
+            false
+        })
+        .catch_others(|_| true)
+        .execute();
+        assert!(
+            result,
+            "converting a UUID that is not v1 should raise an error"
+        );
+    }
+
+    #[pg_test]
+    fn test_pgx_uuid_name_based() {
+        // the same values as Python's uuid.uuid3 / uuid.uuid5 and uuid-ossp
+        let (v3, v5) = Spi::get_two::<pgrx::Uuid, pgrx::Uuid>(
+            "SELECT uuid_generate_v3(uuid_ns_dns(), 'python.org'), uuid_generate_v5(uuid_ns_dns(), 'python.org');",
+        )
+        .unwrap();
+        let v3: uuid::Uuid = Converter(v3.unwrap()).into();
+        let v5: uuid::Uuid = Converter(v5.unwrap()).into();
+        assert_eq!(v3, uuid::uuid!("6fa459ea-ee8a-3ca4-894e-db77e160355e"));
+        assert_eq!(v5, uuid::uuid!("886313e1-3b8a-5372-9b90-0c9aee199e5d"));
+
+        // text and bytea names give the same UUID
+        let same = Spi::get_one::<bool>(
+            "
+            SELECT uuid_generate_v5(uuid_ns_url(), 'https://www.postgresql.org/')
+                = uuid_generate_v5(uuid_ns_url(), convert_to('https://www.postgresql.org/', 'UTF8'))
+            AND uuid_generate_v3(uuid_ns_oid(), '1.3.6.1')
+                = uuid_generate_v3(uuid_ns_oid(), '\\x312e332e362e31'::bytea);
+            ",
+        )
+        .unwrap();
+        assert_eq!(same, Some(true));
+
+        let namespaces = [
+            (uuid_ns_dns(), "6ba7b810-9dad-11d1-80b4-00c04fd430c8"),
+            (uuid_ns_url(), "6ba7b811-9dad-11d1-80b4-00c04fd430c8"),
+            (uuid_ns_oid(), "6ba7b812-9dad-11d1-80b4-00c04fd430c8"),
+            (uuid_ns_x500(), "6ba7b814-9dad-11d1-80b4-00c04fd430c8"),
+        ];
+        for (ns, expected) in namespaces {
+            let ns: uuid::Uuid = Converter(ns).into();
+            assert_eq!(ns.to_string(), expected);
+        }
+    }
+
+    #[pg_test]
+    fn test_pgx_uuidv8() {
+        let u: uuid::Uuid =
+            Converter(uuid_generate_v8(0xffff_ffff_ffff, 0xfff, (1 << 62) - 1)).into();
+        assert_eq!(u, uuid::uuid!("ffffffff-ffff-8fff-bfff-ffffffffffff"));
+        let u: uuid::Uuid = Converter(uuid_generate_v8(
+            0x1234_5678_9abc,
+            0xdef,
+            0x0123_4567_89ab_cdef,
+        ))
+        .into();
+        assert_eq!(u, uuid::uuid!("12345678-9abc-8def-8123-456789abcdef"));
+        assert_eq!(Version::Custom, u.get_version().unwrap());
+        assert_eq!(Variant::RFC4122, u.get_variant());
+
+        // the version and variant bits of custom bytes are overwritten
+        let u: uuid::Uuid = Converter(uuid_generate_v8_bytea(&[0xff; 16])).into();
+        assert_eq!(u, uuid::uuid!("ffffffff-ffff-8fff-bfff-ffffffffffff"));
+
+        for (a, b, c) in [
+            (-1, 0, 0),
+            (1 << 48, 0, 0),
+            (0, -1, 0),
+            (0, 0x1000, 0),
+            (0, 0, 1 << 62),
+        ] {
+            let result = PgTryBuilder::new(|| {
+                uuid_generate_v8(a, b, c);
+                false
+            })
+            .catch_others(|_| true)
+            .execute();
+            assert!(result, "({a}, {b}, {c}) should be out of range");
+        }
+        let result = PgTryBuilder::new(|| {
+            uuid_generate_v8_bytea(&[0; 15]);
+            false
+        })
+        .catch_others(|_| true)
+        .execute();
+        assert!(
+            result,
+            "custom bytes that are not 16 bytes long should raise an error"
+        );
+    }
+
+    #[pg_test]
+    fn test_uuid_inspect() {
+        // RFC 9562 appendix A.6 test vector
+        let v7 = "uuid_inspect('017f22e2-79b0-7cc3-98c4-dc0c0c07398f')";
+        let v1 = "uuid_inspect('c232ab00-9414-11ec-b3c8-9f6bdeced846')";
+        let v6 = "uuid_inspect('1ec9414c-232a-6b00-b3c8-9f6bdeced846')";
+        let ncs = "uuid_inspect('017f22e2-79b0-7cc3-18c4-dc0c0c07398f')";
+        let ms = "uuid_inspect('017f22e2-79b0-7cc3-c8c4-dc0c0c07398f')";
+        let nil = "uuid_inspect('00000000-0000-0000-0000-000000000000')";
+        let max = "uuid_inspect('ffffffff-ffff-ffff-ffff-ffffffffffff')";
+        assert_sql(&[
+            &format!("({v7}).version = 7 AND ({v7}).variant = 'RFC'"),
+            &format!("({v7}).ts = '2022-02-22T19:22:22+00:00'"),
+            &format!("({v7}).rand_a = x'cc3'::int AND ({v7}).rand_b = x'18c4dc0c0c07398f'::bigint"),
+            &format!("({v7}).clock_seq IS NULL AND ({v7}).node IS NULL"),
+            &format!("({v1}).version = 1 AND ({v1}).ts = '2022-02-22T19:22:22+00:00'"),
+            &format!("({v1}).clock_seq = x'33c8'::int AND ({v1}).node = '\\x9f6bdeced846'"),
+            &format!("({v1}).rand_a IS NULL AND ({v1}).rand_b IS NULL"),
+            &format!("({v6}).version = 6 AND ({v6}).clock_seq = x'33c8'::int"),
+            &format!("({v6}).node = '\\x9f6bdeced846'"),
+            &format!("({ncs}).variant = 'NCS' AND ({ncs}).ts IS NULL AND ({ncs}).rand_a IS NULL"),
+            &format!("({ms}).variant = 'Microsoft' AND ({ms}).ts IS NULL"),
+            &format!("({nil}).is_nil AND NOT ({nil}).is_max AND ({nil}).variant = 'NCS'"),
+            &format!("({max}).is_max AND NOT ({max}).is_nil AND ({max}).variant = 'Future'"),
+            // a single row, which also expands into columns
+            &format!("(SELECT count(*) FROM {v7}) = 1"),
+            &format!("(SELECT version FROM {v7}) = 7"),
+        ]);
+    }
+
+    #[pg_test]
+    fn test_clock_seq() {
+        let clock_seq = generator::ClockSeq::new();
+        // the first timestamp takes the seed, truncated to 14 bits
+        assert_eq!(clock_seq.advance(1_000, 0xffff), 0x3fff);
+        // a later timestamp keeps it
+        assert_eq!(clock_seq.advance(2_000, 7), 0x3fff);
+        // the same or an earlier timestamp increments it
+        assert_eq!(clock_seq.advance(2_000, 7), 0);
+        assert_eq!(clock_seq.advance(1_500, 7), 1);
+        assert_eq!(clock_seq.advance(1_600, 7), 1);
+    }
+
     #[pg_test]
     fn test_pgx_uuidv7_min() {
         let pt = gen_pt();
@@ -186,6 +679,368 @@ mod tests {
         assert!(ret2[0].as_ref().unwrap() == "b");
     }
 
+    #[pg_test]
+    fn test_uuid_to_tstzrange() {
+        assert_sql(&[
+            "uuid_to_tstzrange(uuid_generate_v7('2012-03-04T05:06:07.123456+00:00'))
+                = '[2012-03-04 05:06:07.123+00, 2012-03-04 05:06:07.124+00)'::tstzrange",
+            "uuid_to_tstzrange('6ba7b810-9dad-11d1-80b4-00c04fd430c8')
+                @> uuid_to_timestamptz('6ba7b810-9dad-11d1-80b4-00c04fd430c8')",
+            "uuid_to_tstzrange(gen_random_uuid()) IS NULL",
+        ]);
+    }
+
+    #[pg_test]
+    fn test_tstzrange_to_uuid_range() {
+        // the bounds agree with the <@ operator for every kind of range
+        let mismatches = Spi::get_one::<i64>(
+            "
+            WITH ids AS (
+                SELECT uuid_generate_v7(
+                    '2012-03-04T05:06:07+00:00'::timestamptz + n * INTERVAL '250 microseconds'
+                ) AS u
+                FROM generate_series(0, 40) AS n
+            ), ranges AS (
+                SELECT tstzrange(
+                    '2012-03-04 05:06:07.0025+00', '2012-03-04 05:06:07.0075+00', b
+                ) AS r
+                FROM unnest(ARRAY['[)', '[]', '(]', '()']) AS b
+                UNION ALL SELECT tstzrange(NULL, '2012-03-04 05:06:07.005+00')
+                UNION ALL SELECT tstzrange('2012-03-04 05:06:07.005+00', NULL)
+                UNION ALL SELECT tstzrange('-infinity', 'infinity', '()')
+                UNION ALL SELECT tstzrange('1960-01-01', '2012-03-04 05:06:07.005+00', '()')
+                UNION ALL SELECT tstzrange('2012-03-04 05:06:07.005+00', '20000-01-01', '[]')
+                UNION ALL SELECT tstzrange('1950-01-01', '1960-01-01')
+                UNION ALL SELECT tstzrange('2012-03-04 05:06:07.0002+00', '2012-03-04 05:06:07.0008+00')
+                UNION ALL SELECT tstzrange('2012-03-04 05:06:07+00', '2012-03-04 05:06:07.001+00', '()')
+            )
+            SELECT count(*)
+            FROM ids, ranges, tstzrange_to_uuid_range(r) AS b
+            WHERE (u BETWEEN b.lower AND b.upper) IS DISTINCT FROM (u <@ r);
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(mismatches, 0);
+
+        let empty =
+            Spi::get_one::<bool>("SELECT (tstzrange_to_uuid_range('empty')).lower IS NULL;")
+                .unwrap()
+                .unwrap();
+        assert!(empty);
+
+        // bounds outside the range of UUIDv7 become the nil or the max UUID
+        assert_sql(&[
+            "(tstzrange_to_uuid_range('[-infinity, infinity]')).lower = uuid_nil()",
+            "(tstzrange_to_uuid_range('[-infinity, infinity]')).upper = uuid_max()",
+            "(tstzrange_to_uuid_range('(-infinity, infinity)')).lower = uuid_nil()",
+            "(tstzrange_to_uuid_range('(-infinity, infinity)')).upper = uuid_max()",
+            "(tstzrange_to_uuid_range('[1960-01-01, 2012-03-04 05:06:07+00)')).lower = uuid_nil()",
+            "(tstzrange_to_uuid_range('[2012-03-04 05:06:07+00, 20000-01-01)')).upper = uuid_max()",
+        ]);
+
+        // a range that holds no whole millisecond gives inverted bounds
+        assert_sql(&[
+            "(SELECT lower > upper FROM tstzrange_to_uuid_range(
+                '[2012-03-04 05:06:07.0002+00, 2012-03-04 05:06:07.0008+00)'))",
+            "(SELECT lower > upper FROM tstzrange_to_uuid_range(
+                '(2012-03-04 05:06:07+00, 2012-03-04 05:06:07.001+00)'))",
+        ]);
+    }
+
+    #[pg_test]
+    fn test_uuidrange() {
+        assert_sql(&[
+            "uuid_subtype_diff(
+                timestamptz_to_uuid_v7_min('2012-03-04T05:06:08+00:00'),
+                timestamptz_to_uuid_v7_min('2012-03-04T05:06:07+00:00')
+            ) = 1000",
+            "uuid_subtype_diff(
+                timestamptz_to_uuid_v7_min('2012-03-04T05:06:07+00:00'),
+                timestamptz_to_uuid_v7_max('2012-03-04T05:06:07+00:00')
+            ) BETWEEN -1 AND 0",
+            // UUIDs that differ only in the last bit are not rounded to the same value
+            "uuid_subtype_diff(
+                'ffffffff-ffff-7fff-bfff-ffffffffffff', 'ffffffff-ffff-7fff-bfff-fffffffffffe'
+            ) > 0",
+            "uuid_subtype_diff(
+                'ffffffff-ffff-7fff-bfff-fffffffffffe', 'ffffffff-ffff-7fff-bfff-ffffffffffff'
+            ) < 0",
+            "tstzrange_to_uuidrange('[2012-03-04 05:06:07+00, 2012-03-04 05:06:08+00)')
+                = uuidrange(
+                    timestamptz_to_uuid_v7_min('2012-03-04T05:06:07+00:00'),
+                    timestamptz_to_uuid_v7_max('2012-03-04T05:06:07.999+00:00'),
+                    '[]'
+                )",
+            "timestamptz_to_uuidrange('2012-03-04 05:06:07+00', '2012-03-04 05:06:08+00')
+                @> uuid_generate_v7('2012-03-04T05:06:07.5+00:00')",
+            "NOT timestamptz_to_uuidrange('2012-03-04 05:06:07+00', '2012-03-04 05:06:08+00')
+                @> uuid_generate_v7('2012-03-04T05:06:08+00:00')",
+            "isempty(tstzrange_to_uuidrange('empty'))",
+            // ranges that hold no whole millisecond
+            "isempty(timestamptz_to_uuidrange(
+                '2012-03-04 05:06:07.0002+00', '2012-03-04 05:06:07.0008+00'
+            ))",
+            "isempty(tstzrange_to_uuidrange('(2012-03-04 05:06:07+00, 2012-03-04 05:06:07.001+00)'))",
+            "NOT isempty(tstzrange_to_uuidrange('[2012-03-04 05:06:07+00, 2012-03-04 05:06:07.001+00)'))",
+            "lower_inf(tstzrange_to_uuidrange('(,2012-03-04 05:06:07+00)'))",
+            "upper_inf(tstzrange_to_uuidrange('[2012-03-04 05:06:07+00,)'))",
+        ]);
+    }
+
+    #[pg_test]
+    fn test_uuidrange_indexes() {
+        Spi::run(
+            "
+            CREATE TEMP TABLE shard_windows (
+                shard int,
+                ids uuidrange,
+                EXCLUDE USING gist (ids WITH &&)
+            );
+            CREATE INDEX ON shard_windows USING spgist (ids);
+            INSERT INTO shard_windows
+            SELECT n, timestamptz_to_uuidrange(
+                '2012-03-04T00:00:00+00:00'::timestamptz + n * INTERVAL '1 hour',
+                '2012-03-04T00:00:00+00:00'::timestamptz + (n + 1) * INTERVAL '1 hour'
+            )
+            FROM generate_series(0, 23) AS n;
+            ",
+        )
+        .unwrap();
+
+        let shard = Spi::get_one::<i32>(
+            "
+            SELECT shard FROM shard_windows
+            WHERE ids @> uuid_generate_v7('2012-03-04T05:06:07+00:00');
+            ",
+        )
+        .unwrap();
+        assert_eq!(shard, Some(5));
+
+        // adjacent windows do not overlap, but overlapping ones are excluded
+        let result = PgTryBuilder::new(|| {
+            Spi::run(
+                "
+                INSERT INTO shard_windows VALUES (
+                    99,
+                    timestamptz_to_uuidrange('2012-03-04 05:30:00+00', '2012-03-04 06:30:00+00')
+                );
+                ",
+            )
+            .unwrap();
+            false
+        })
+        .catch_others(|_| true)
+        .execute();
+        assert!(result, "an overlapping window should be rejected");
+    }
+
+    /// Assert that each SQL boolean expression is true, naming the first one that is not.
+    fn assert_sql(exprs: &[&str]) {
+        for expr in exprs {
+            let result = Spi::get_one::<bool>(&format!("SELECT {expr}")).unwrap();
+            assert_eq!(result, Some(true), "{expr}");
+        }
+    }
+
+    fn explain(query: &str) -> String {
+        Spi::connect(|client| {
+            client
+                .select(&format!("EXPLAIN (COSTS OFF) {query}"), None, &[])
+                .unwrap()
+                .map(|row| row[1].value::<String>().unwrap().unwrap())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+
+    #[pg_test]
+    fn test_uuid_timestamptz_operators() {
+        assert_sql(&[
+            "timestamptz_to_uuid_v7_min_clamped('-infinity') = uuid_nil()",
+            "timestamptz_to_uuid_v7_max_clamped('1969-12-31T23:59:59.999999+00:00') = uuid_nil()",
+            "timestamptz_to_uuid_v7_min_clamped('10889-08-02 05:31:50.656+00') = uuid_max()",
+            "timestamptz_to_uuid_v7_max_clamped('infinity') = uuid_max()",
+            "timestamptz_to_uuid_v7_min_clamped('1970-01-01T00:00:00+00:00')
+                = timestamptz_to_uuid_v7_min('1970-01-01T00:00:00+00:00')",
+            "timestamptz_to_uuid_v7_max_clamped('10889-08-02 05:31:50.655999+00')
+                = timestamptz_to_uuid_v7_max('10889-08-02 05:31:50.655+00')",
+        ]);
+
+        let ts = "'2012-03-04T05:06:07.123456+00:00'::timestamptz";
+        for u in [
+            "uuid_generate_v7('2012-03-04T05:06:07.123456+00:00')",
+            "uuid_generate_v7('2012-03-04T05:06:07.123456+00:00')::uuidv7",
+        ] {
+            assert_sql(&[
+                &format!("NOT ({u} < {ts})"),
+                &format!("{u} <= {ts}"),
+                &format!("NOT ({u} > {ts})"),
+                &format!("{u} >= {ts}"),
+                &format!("NOT ({ts} > {u})"),
+                &format!("{ts} >= {u}"),
+                &format!("NOT ({ts} < {u})"),
+                &format!("{ts} <= {u}"),
+                &format!("{u} < {ts} + INTERVAL '1 millisecond'"),
+                &format!("{u} > {ts} - INTERVAL '1 millisecond'"),
+                &format!("{u} <@ tstzrange({ts}, {ts} + INTERVAL '1 millisecond')"),
+                &format!("NOT ({u} <@ tstzrange({ts}, {ts}, '[)'))"),
+                &format!("NOT ({u} <@ tstzrange({ts}, NULL, '()'))"),
+                &format!("{u} <@ tstzrange(NULL, {ts}, '(]')"),
+                &format!("{u} <@ tstzrange(NULL, NULL)"),
+                &format!("tstzrange({ts} - INTERVAL '1 day', {ts} + INTERVAL '1 day') @> {u}"),
+                // timestamps outside the range of UUIDv7 compare without an error
+                &format!("{u} < 'infinity'::timestamptz"),
+                &format!("NOT ({u} >= 'infinity'::timestamptz)"),
+                &format!("{u} >= '-infinity'::timestamptz"),
+                &format!("NOT ({u} <= '-infinity'::timestamptz)"),
+                &format!("{u} > '1960-01-01T00:00:00+00:00'::timestamptz"),
+                &format!("'1960-01-01T00:00:00+00:00'::timestamptz < {u}"),
+                &format!("NOT ({u} <= '1960-01-01T00:00:00+00:00'::timestamptz)"),
+                &format!("{u} <= '20000-01-01T00:00:00+00:00'::timestamptz"),
+                &format!("'20000-01-01T00:00:00+00:00'::timestamptz > {u}"),
+                &format!("{u} <@ tstzrange('1960-01-01T00:00:00+00:00', now())"),
+                &format!("{u} <@ tstzrange('-infinity', 'infinity')"),
+                &format!("{u} <@ tstzrange(NULL, '20000-01-01T00:00:00+00:00')"),
+                &format!(
+                    "NOT ({u} <@ tstzrange('1950-01-01T00:00:00+00:00', '1960-01-01T00:00:00+00:00'))"
+                ),
+            ]);
+        }
+    }
+
+    #[pg_test]
+    fn test_uuid_timestamptz_operators_use_index() {
+        Spi::run(
+            "
+            CREATE TEMP TABLE op_index_test (id uuid PRIMARY KEY, id7 uuidv7 UNIQUE);
+            INSERT INTO op_index_test
+            SELECT u, u
+            FROM (
+                SELECT uuid_generate_v7(
+                    '2012-03-04T05:06:07+00:00'::timestamptz + n * INTERVAL '1 second'
+                ) AS u
+                FROM generate_series(1, 1000) AS n
+            ) t;
+            ANALYZE op_index_test;
+            SET LOCAL enable_seqscan = off;
+            ",
+        )
+        .unwrap();
+
+        let ts = "'2012-03-04T05:10:00+00:00'::timestamptz";
+        let range = "'[2012-03-04 05:10:00+00, 2012-03-04 05:11:00+00)'::tstzrange";
+        let mut queries = Vec::new();
+        for column in ["id", "id7"] {
+            for op in ["<", "<=", ">", ">="] {
+                queries.push(format!(
+                    "SELECT * FROM op_index_test WHERE {column} {op} {ts}"
+                ));
+                queries.push(format!(
+                    "SELECT * FROM op_index_test WHERE {ts} {op} {column}"
+                ));
+            }
+            queries.push(format!(
+                "SELECT * FROM op_index_test WHERE {column} <@ {range}"
+            ));
+            queries.push(format!(
+                "SELECT * FROM op_index_test WHERE {range} @> {column}"
+            ));
+            queries.push(format!(
+                "SELECT * FROM op_index_test WHERE {column} < 'infinity'::timestamptz"
+            ));
+            queries.push(format!(
+                "SELECT * FROM op_index_test WHERE {column} <@ tstzrange('1960-01-01', {ts})"
+            ));
+        }
+        for query in &queries {
+            let plan = explain(query);
+            assert!(
+                plan.contains("Index Cond"),
+                "Expected an index scan for {query}, got:\n{plan}"
+            );
+        }
+
+        let count = Spi::get_one::<i64>(
+            "
+            SELECT count(*) FROM op_index_test
+            WHERE id <@ '[2012-03-04 05:10:00+00, 2012-03-04 05:11:00+00)'::tstzrange;
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(count, 60);
+
+        for (condition, expected) in [
+            ("id7 >= '-infinity'::timestamptz", 1000),
+            ("id < 'infinity'::timestamptz", 1000),
+            (
+                "id <@ '[1960-01-01 00:00:00+00, 2012-03-04 05:06:11+00)'::tstzrange",
+                3,
+            ),
+            (
+                "id7 <@ '[1960-01-01 00:00:00+00, 2012-03-04 05:06:11+00)'::tstzrange",
+                3,
+            ),
+        ] {
+            let count = Spi::get_one::<i64>(&format!(
+                "SELECT count(*) FROM op_index_test WHERE {condition}"
+            ))
+            .unwrap()
+            .unwrap();
+            assert_eq!(count, expected, "{condition}");
+        }
+    }
+
+    #[pg_test]
+    fn test_uuidv7_brin() {
+        Spi::run(
+            "
+            CREATE TEMP TABLE brin_test (id uuidv7, id_multi uuidv7, id_bloom uuidv7);
+            INSERT INTO brin_test
+            SELECT u, u, u
+            FROM (
+                SELECT uuid_generate_v7(
+                    '2012-03-04T05:06:07+00:00'::timestamptz + n * INTERVAL '1 second'
+                )::uuidv7 AS u
+                FROM generate_series(1, 10000) AS n
+            ) t;
+            CREATE INDEX ON brin_test USING brin (id) WITH (pages_per_range = 1);
+            CREATE INDEX ON brin_test USING brin (id_multi uuidv7_minmax_multi_ops)
+                WITH (pages_per_range = 1);
+            CREATE INDEX ON brin_test USING brin (id_bloom uuidv7_bloom_ops)
+                WITH (pages_per_range = 1);
+            ANALYZE brin_test;
+            SET LOCAL enable_seqscan = off;
+            ",
+        )
+        .unwrap();
+
+        for column in ["id", "id_multi"] {
+            let query = format!(
+                "SELECT count(*) FROM brin_test
+                WHERE {column} <@ '[2012-03-04 05:10:00+00, 2012-03-04 05:11:00+00)'::tstzrange"
+            );
+            let plan = explain(&query);
+            assert!(
+                plan.contains("Bitmap Index Scan"),
+                "Expected a BRIN index scan on {column}, got:\n{plan}"
+            );
+            let count = Spi::get_one::<i64>(&query).unwrap().unwrap();
+            assert_eq!(count, 60);
+        }
+
+        let query = "SELECT count(*) FROM brin_test
+            WHERE id_bloom = (SELECT id FROM brin_test ORDER BY id OFFSET 5000 LIMIT 1)";
+        let plan = explain(query);
+        assert!(
+            plan.contains("Bitmap Index Scan"),
+            "Expected a BRIN index scan on id_bloom, got:\n{plan}"
+        );
+        let count = Spi::get_one::<i64>(query).unwrap().unwrap();
+        assert_eq!(count, 1);
+    }
+
     #[pg_test]
     fn test_invalid_uuid_version() {
         // Test with UUID v4 (not v7) - should return NULL
@@ -216,9 +1071,9 @@ mod tests {
                 .unwrap();
 
         // Verify all are version 7
-        assert_eq!(uuid_get_version(uuid_past), 7);
-        assert_eq!(uuid_get_version(uuid_now), 7);
-        assert_eq!(uuid_get_version(uuid_future), 7);
+        assert_eq!(uuid_get_version(uuid_past), Some(7));
+        assert_eq!(uuid_get_version(uuid_now), Some(7));
+        assert_eq!(uuid_get_version(uuid_future), Some(7));
 
         // Extract timestamps
         let ts_past = uuid_to_timestamptz(uuid_past).unwrap();
@@ -300,6 +1155,16 @@ mod tests {
             Spi::get_one::<pgrx::Uuid>("SELECT timestamptz_to_uuid_v7_min(NULL::timestamptz);")
                 .unwrap();
         assert!(null_min_result.is_none());
+
+        // Test NULL input for uuid_generate_v1, which is not strict because of its default node
+        let null_v1_result =
+            Spi::get_one::<pgrx::Uuid>("SELECT uuid_generate_v1(NULL::timestamptz);").unwrap();
+        assert!(null_v1_result.is_none());
+
+        // Test NULL input for uuid_generate_v6, which is not strict because of its default node
+        let null_v6_result =
+            Spi::get_one::<pgrx::Uuid>("SELECT uuid_generate_v6(NULL::timestamptz);").unwrap();
+        assert!(null_v6_result.is_none());
     }
 
     #[pg_test]
@@ -371,7 +1236,13 @@ mod tests {
         // Test uuidv7() alias
         let uuid_v7 = uuidv7();
         let version = uuid_extract_version(uuid_v7);
-        assert_eq!(version, 7i16);
+        assert_eq!(version, Some(7i16));
+
+        // Test uuidv4() alias
+        let uuid_v4 = uuidv4();
+        assert_eq!(uuid_extract_version(uuid_v4), Some(4i16));
+        let u: uuid::Uuid = Converter(uuid_v4).into();
+        assert_eq!(Variant::RFC4122, u.get_variant());
 
         // Test that aliases produce same results as original functions
         let uuid_orig = uuid_generate_v7_now();
@@ -379,7 +1250,7 @@ mod tests {
 
         let version_orig = uuid_get_version(uuid_orig);
         let version_alias = uuid_extract_version(uuid_orig);
-        assert_eq!(version_orig as i16, version_alias);
+        assert_eq!(version_orig.map(i16::from), version_alias);
 
         // uuid_extract_timestamp is only available for PG < 17
         #[cfg(not(feature = "pg17"))]
@@ -404,7 +1275,7 @@ mod tests {
 
         let uuid_past = result.unwrap();
         let version = uuid_extract_version(uuid_past);
-        assert_eq!(version, 7i16);
+        assert_eq!(version, Some(7i16));
 
         // Just verify that timestamp extraction works
         let timestamp = uuid_to_timestamptz(uuid_past);
@@ -427,8 +1298,8 @@ mod tests {
             .unwrap();
 
         // Verify all are version 7
-        assert_eq!(uuid_extract_version(uuid_past), 7i16);
-        assert_eq!(uuid_extract_version(uuid_now), 7i16);
+        assert_eq!(uuid_extract_version(uuid_past), Some(7i16));
+        assert_eq!(uuid_extract_version(uuid_now), Some(7i16));
 
         // Verify timestamps can be extracted
         let ts_past = uuid_to_timestamptz(uuid_past);
@@ -487,6 +1358,16 @@ mod tests {
                 fixture_uuid(0x2468_ACE0_1357_9BDF_0246_8ACE_1357_9BDF, Version::Sha1),
                 fixture_uuid(0x0F0E_0D0C_0B0A_0908_0706_0504_0302_0100, Version::Random),
             ),
+            (
+                "uuidv6",
+                fixture_uuid(0x1EC9_414C_232A_6B00_B3C8_9F6B_DECE_D846, Version::SortMac),
+                fixture_uuid(0xC232_AB00_9414_11EC_B3C8_9F6B_DECE_D846, Version::Mac),
+            ),
+            (
+                "uuidv8",
+                fixture_uuid(0x2489_E9AD_2EE2_8E00_8EC9_32D5_F691_81C0, Version::Custom),
+                fixture_uuid(0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210, Version::SortRand),
+            ),
             (
                 "uuidv7",
                 fixture_uuid(0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210, Version::SortRand),
@@ -522,9 +1403,510 @@ mod tests {
                 table = table,
                 invalid_uuid = invalid_uuid
             );
+
+            let mut builder = uuid::Builder::from_u128(valid_uuid.as_u128());
+            builder.set_variant(Variant::NCS);
+            let wrong_variant = builder.into_uuid();
+            assert!(
+                !insert_into_domain(&table, wrong_variant),
+                "Expected insert into {table} to fail for UUID {wrong_variant} with a wrong variant"
+            );
         }
     }
 
+    #[pg_test]
+    fn test_uuid_nil_max() {
+        assert_sql(&[
+            "uuid_nil() = '00000000-0000-0000-0000-000000000000'",
+            "uuid_max() = 'ffffffff-ffff-ffff-ffff-ffffffffffff'",
+            "uuid_is_nil(uuid_nil()) AND NOT uuid_is_nil(uuid_max())",
+            "uuid_is_max(uuid_max()) AND NOT uuid_is_max(uuid_nil())",
+            "NOT uuid_is_nil(uuid_generate_v7_now()) AND NOT uuid_is_max(uuid_generate_v7_now())",
+            "uuid_get_version(uuid_nil()) IS NULL",
+            "uuid_get_version(uuid_max()) IS NULL",
+            // the ends of the 48-bit timestamp range
+            "timestamptz_to_uuid_v7_min('1970-01-01T00:00:00+00:00')
+                = '00000000-0000-7000-8000-000000000000'",
+            "timestamptz_to_uuid_v7_max('10889-08-02 05:31:50.655+00')
+                = 'ffffffff-ffff-7fff-bfff-ffffffffffff'",
+            // nil and max bound every UUIDv7, so they work as open cursors
+            "uuid_nil() < timestamptz_to_uuid_v7_min('1970-01-01T00:00:00+00:00')",
+            "uuid_max() > timestamptz_to_uuid_v7_max('10889-08-02 05:31:50.655+00')",
+        ]);
+    }
+
+    // Run the query and return the SQLSTATE of the error it raises, if any.
+    fn error_code(query: &str) -> Option<PgSqlErrorCode> {
+        PgTryBuilder::new(|| {
+            Spi::run(query).unwrap();
+            None
+        })
+        .catch_others(|e| match e {
+            pg_sys::panic::CaughtError::PostgresError(report)
+            | pg_sys::panic::CaughtError::ErrorReport(report) => Some(report.sql_error_code()),
+            _ => Some(PgSqlErrorCode::ERRCODE_INTERNAL_ERROR),
+        })
+        .execute()
+    }
+
+    #[pg_test]
+    fn test_timestamp_out_of_range() {
+        let overflow = Some(PgSqlErrorCode::ERRCODE_DATETIME_FIELD_OVERFLOW);
+
+        // error is the default
+        for query in [
+            "SELECT uuid_generate_v7('1960-01-01T00:00:00+00:00')",
+            "SELECT uuid_generate_v7('10889-08-02 05:31:50.656+00')",
+            "SELECT uuid_generate_v7('infinity')",
+            "SELECT uuid_generate_v7_counter('-infinity')",
+            "SELECT uuid_generate_v7_batch(2, '1969-12-31T23:59:59.999+00:00')",
+            "SELECT timestamptz_to_uuid_v7_min('1960-01-01T00:00:00+00:00')",
+            "SELECT uuid_generate_v1('1582-10-14T00:00:00+00:00')",
+            "SELECT uuid_generate_v6('5236-04-01T00:00:00+00:00')",
+        ] {
+            assert_eq!(error_code(query), overflow, "{query}");
+        }
+        // the ends of the ranges are accepted
+        for query in [
+            "SELECT uuid_generate_v7('1970-01-01T00:00:00+00:00')",
+            "SELECT uuid_generate_v7('10889-08-02 05:31:50.655+00')",
+            "SELECT uuid_generate_v1('1582-10-15T00:00:00+00:00')",
+            "SELECT uuid_generate_v1('1960-01-01T00:00:00+00:00')",
+        ] {
+            assert_eq!(error_code(query), None, "{query}");
+        }
+
+        Spi::run("SET LOCAL pgx_uuidv7.timestamp_out_of_range = 'clamp';").unwrap();
+        assert_sql(&[
+            "uuid_to_timestamptz(uuid_generate_v7('1960-01-01T00:00:00+00:00'))
+                = '1970-01-01T00:00:00+00:00'",
+            "uuid_to_timestamptz(uuid_generate_v7('-infinity'))
+                = '1970-01-01T00:00:00+00:00'",
+            "uuid_to_timestamptz(uuid_generate_v7('20000-01-01 00:00:00+00'))
+                = '10889-08-02 05:31:50.655+00'",
+            "uuid_to_timestamptz(uuid_generate_v7_counter('infinity'))
+                = '10889-08-02 05:31:50.655+00'",
+        ]);
+        // the immutable bounds always raise an error
+        assert_eq!(
+            error_code("SELECT timestamptz_to_uuid_v7_max('1960-01-01T00:00:00+00:00')"),
+            overflow
+        );
+
+        Spi::run("SET LOCAL pgx_uuidv7.timestamp_out_of_range = 'wrap';").unwrap();
+        assert_sql(&[
+            "uuid_to_timestamptz(uuid_generate_v7('1969-12-31T23:59:59.999+00:00'))
+                = '10889-08-02 05:31:50.655+00'",
+            "uuid_to_timestamptz(uuid_generate_v7('10889-08-02 05:31:50.656+00'))
+                = '1970-01-01T00:00:00+00:00'",
+        ]);
+        assert_eq!(error_code("SELECT uuid_generate_v7('infinity')"), overflow);
+    }
+
+    #[pg_test]
+    fn test_timestamp_precision() {
+        // millisecond is the default
+        assert_sql(&[
+            "uuid_to_timestamptz_precise(uuid_generate_v7('2012-03-04T05:06:07.123456+00:00'))
+                BETWEEN '2012-03-04T05:06:07.123+00:00' AND '2012-03-04T05:06:07.123999+00:00'",
+        ]);
+
+        Spi::run("SET LOCAL pgx_uuidv7.timestamp_precision = 'microsecond';").unwrap();
+        let mismatches = Spi::get_one::<String>(
+            "
+            SELECT string_agg(ts::text, ', ')
+            FROM (
+                SELECT '2012-03-04T05:06:07+00:00'::timestamptz + make_interval(secs => n / 1000000.0) AS ts
+                FROM generate_series(0, 2999) AS n
+                UNION ALL
+                SELECT ts::timestamptz FROM unnest(ARRAY[
+                    '1970-01-01T00:00:00+00:00',
+                    '10889-08-02 05:31:50.655999+00'
+                ]) AS ts
+            ) AS t
+            WHERE uuid_to_timestamptz_precise(uuid_generate_v7(ts)) <> ts;
+            ",
+        )
+        .unwrap();
+        assert_eq!(mismatches, None, "microsecond round trip should hold");
+        // the millisecond part is unchanged, and the UUIDs still sort by timestamp
+        assert_sql(&[
+            "uuid_to_timestamptz(uuid_generate_v7('2012-03-04T05:06:07.123999+00:00'))
+                = '2012-03-04T05:06:07.123+00:00'",
+            "uuid_generate_v7('2012-03-04T05:06:07.123455+00:00')
+                < uuid_generate_v7('2012-03-04T05:06:07.123456+00:00')",
+            "uuid_is_valid_v7(uuid_generate_v7('2012-03-04T05:06:07.123999+00:00'))",
+            "uuid_to_timestamptz_precise('c232ab00-9414-11ec-b3c8-9f6bdeced846')
+                = uuid_to_timestamptz('c232ab00-9414-11ec-b3c8-9f6bdeced846')",
+        ]);
+    }
+
+    #[pg_test]
+    fn test_uuid_to_timestamptz_ranges() {
+        assert_sql(&[
+            "uuid_to_timestamptz(uuid_generate_v1('1582-10-15T00:00:00+00:00'))
+                = '1582-10-15T00:00:00+00:00'",
+            "uuid_to_timestamptz(uuid_generate_v1('1960-01-01T00:00:00.000001+00:00'))
+                = '1960-01-01T00:00:00.000001+00:00'",
+            "uuid_to_timestamptz(uuid_generate_v6('1960-01-01T00:00:00.000001+00:00'))
+                = '1960-01-01T00:00:00.000001+00:00'",
+            "uuid_to_timestamptz(uuid_generate_v6('5236-03-31 21:21:00.684697+00'))
+                = '5236-03-31 21:21:00.684697+00'",
+            "uuid_to_timestamptz(uuid_generate_v7('10889-08-02 05:31:50.655+00'))
+                = '10889-08-02 05:31:50.655+00'",
+            "uuid_to_tstzrange(uuid_generate_v1('1960-01-01T00:00:00.000999+00:00'))
+                = '[1960-01-01T00:00:00+00:00, 1960-01-01T00:00:00.001+00:00)'::tstzrange",
+            "uuid_to_timestamptz(gen_random_uuid()) IS NULL",
+        ]);
+    }
+
+    #[pg_test]
+    fn test_ulid_text() {
+        assert_sql(&[
+            "uuid_to_ulid_text('01563e3a-b5d3-d676-4c61-efb99302bd5b')
+                = '01ARZ3NDEKTSV4RRFFQ69G5FAV'",
+            "ulid_text_to_uuid('01ARZ3NDEKTSV4RRFFQ69G5FAV')
+                = '01563e3a-b5d3-d676-4c61-efb99302bd5b'",
+            "ulid_text_to_uuid('01arz3ndektsv4rrffq69g5fav')
+                = '01563e3a-b5d3-d676-4c61-efb99302bd5b'",
+            "ulid_text_to_uuid('OLARZ3NDEKTSV4RRFFQ69G5FAV')
+                = '01563e3a-b5d3-d676-4c61-efb99302bd5b'",
+            "uuid_to_ulid_text(uuid_nil()) = '00000000000000000000000000'",
+            "uuid_to_ulid_text(uuid_max()) = '7ZZZZZZZZZZZZZZZZZZZZZZZZZ'",
+            "ulid_text_to_uuid('7ZZZZZZZZZZZZZZZZZZZZZZZZZ') = uuid_max()",
+            // the ULID timestamp is the UUIDv7 timestamp
+            "left(uuid_to_ulid_text(uuid_generate_v7('2016-07-30T23:54:10.259+00:00')), 10)
+                = '01ARZ3NDEK'",
+            // ULIDs sort like the UUIDs
+            "uuid_to_ulid_text(uuid_generate_v7('2012-03-04T05:06:07+00:00')) COLLATE \"C\"
+                < uuid_to_ulid_text(uuid_generate_v7('2012-03-04T05:06:08+00:00'))",
+        ]);
+
+        for _ in 0..100 {
+            let round_trip = Spi::get_one::<bool>(
+                "
+                WITH u AS (SELECT uuid_generate_v7_now() AS id)
+                SELECT ulid_text_to_uuid(uuid_to_ulid_text(id)) = id FROM u;
+                ",
+            )
+            .unwrap()
+            .unwrap();
+            assert!(round_trip);
+        }
+
+        let invalid = Some(PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION);
+        for ulid in [
+            "01ARZ3NDEKTSV4RRFFQ69G5FA",
+            "01ARZ3NDEKTSV4RRFFQ69G5FAVX",
+            "01ARZ3NDEKTSV4RRFFQ69G5FAU",
+            "80000000000000000000000000",
+            "01ARZ3NDEKTSV4RRFFQ69G5FÄ",
+        ] {
+            assert_eq!(
+                error_code(&format!("SELECT ulid_text_to_uuid('{ulid}')")),
+                invalid,
+                "{ulid}"
+            );
+        }
+    }
+
+    #[pg_test]
+    fn test_uuid_encode_decode() {
+        let u = "'0186a1f2-c4b0-7e1c-8a3f-6b2d9e4c1a0f'::uuid";
+        for (format, text) in [
+            ("base58", "1Bvs7ioUw4zk7b5wo4f4ht"),
+            ("base62", "02sWDLhgKEj2aPfkyDAF7X"),
+            ("base64url", "AYah8sSwfhyKP2stnkwaDw"),
+            ("hex", "0186a1f2c4b07e1c8a3f6b2d9e4c1a0f"),
+            ("braced", "{0186a1f2-c4b0-7e1c-8a3f-6b2d9e4c1a0f}"),
+            ("ulid", "01GTGZ5H5GFRE8MFVB5PF4R6GF"),
+        ] {
+            assert_sql(&[
+                &format!("uuid_encode({u}, '{format}') = '{text}'"),
+                &format!("uuid_decode('{text}', '{format}') = {u}"),
+                &format!("uuid_decode('{text}', upper('{format}')) = {u}"),
+                &format!("bytea_to_uuid(uuid_to_bytea(uuid_decode('{text}', '{format}'))) = {u}"),
+            ]);
+        }
+
+        assert_sql(&[
+            "uuid_encode(uuid_nil(), 'base58') = '1111111111111111111111'",
+            "uuid_encode(uuid_max(), 'base58') = 'YcVfxkQb6JRzqk5kF2tNLv'",
+            "uuid_encode(uuid_max(), 'base62') = '7n42DGM5Tflk9n8mt7Fhc7'",
+            "uuid_encode(uuid_max(), 'base64url') = '_____________________w'",
+            // shorter Base58 and Base62, and padded Base64, are accepted
+            "uuid_decode('2', 'base58') = '00000000-0000-0000-0000-000000000001'",
+            "uuid_decode('Z', 'base62') = '00000000-0000-0000-0000-000000000023'",
+            "uuid_decode('AYah8sSwfhyKP2stnkwaDw==', 'base64url')
+                = '0186a1f2-c4b0-7e1c-8a3f-6b2d9e4c1a0f'",
+            "uuid_decode('0186A1F2C4B07E1C8A3F6B2D9E4C1A0F', 'hex')
+                = '0186a1f2-c4b0-7e1c-8a3f-6b2d9e4c1a0f'",
+            // Base58 and Base62 sort like the UUIDs
+            "uuid_encode(uuid_generate_v7('2012-03-04T05:06:07+00:00'), 'base58') COLLATE \"C\"
+                < uuid_encode(uuid_generate_v7('2012-03-04T05:06:08+00:00'), 'base58')",
+            "uuid_encode(uuid_generate_v7('2012-03-04T05:06:07+00:00'), 'base62') COLLATE \"C\"
+                < uuid_encode(uuid_generate_v7('2012-03-04T05:06:08+00:00'), 'base62')",
+        ]);
+
+        for format in ["base58", "base62", "base64url", "hex", "braced", "ulid"] {
+            let round_trip = Spi::get_one::<bool>(&format!(
+                "
+                SELECT bool_and(uuid_decode(uuid_encode(id, '{format}'), '{format}') = id)
+                FROM (SELECT gen_random_uuid() AS id FROM generate_series(1, 1000)) AS t;
+                "
+            ))
+            .unwrap()
+            .unwrap();
+            assert!(round_trip, "{format} should round-trip");
+        }
+
+        let invalid = Some(PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION);
+        for (format, text) in [
+            ("base58", "0Bvs7ioUw4zk7b5wo4f4ht"),
+            ("base58", "1Bvs7ioUw4zk7b5wo4f4ht1"),
+            ("base58", ""),
+            ("base62", "7n42DGM5Tflk9n8mt7Fhc8"),
+            ("base64url", "AYah8sSwfhyKP2stnkwaDx"),
+            ("base64url", "AYah8sSwfhyKP2stnkwaD+"),
+            ("hex", "0186a1f2-c4b0-7e1c-8a3f-6b2d9e4c1a0f"),
+            ("braced", "0186a1f2-c4b0-7e1c-8a3f-6b2d9e4c1a0f"),
+            ("ulid", "81GTGZ5H5GFRE8MFVB5PF4R6GF"),
+        ] {
+            assert_eq!(
+                error_code(&format!("SELECT uuid_decode('{text}', '{format}')")),
+                invalid,
+                "{format}: {text}"
+            );
+        }
+        assert_eq!(
+            error_code("SELECT uuid_encode(uuid_nil(), 'base32')"),
+            Some(PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE)
+        );
+    }
+
+    #[pg_test]
+    fn test_typeid() {
+        assert_sql(&[
+            // test vectors from the TypeID spec
+            "typeid('prefix', '01890a5d-ac96-774b-bcce-b302099a8057')::text
+                = 'prefix_01h455vb4pex5vsknk084sn02q'",
+            "'prefix_01h455vb4pex5vsknk084sn02q'::typeid::uuid
+                = '01890a5d-ac96-774b-bcce-b302099a8057'",
+            "'00000000000000000000000000'::typeid::uuid = uuid_nil()",
+            "typeid_prefix('00000000000000000000000000') = ''",
+            "typeid_prefix('pre_fix_00000000000000000000000000') = 'pre_fix'",
+            "typeid_to_uuid('pre_fix_7zzzzzzzzzzzzzzzzzzzzzzzzz') = uuid_max()",
+            // generation
+            "typeid_prefix(typeid_generate('user')) = 'user'",
+            "uuid_is_valid_v7(typeid_generate('user')::uuid)",
+            "typeid_generate('user') <> typeid_generate('user')",
+            // parsing with the expected prefix
+            "typeid_parse('user_01h455vb4pex5vsknk084sn02q', 'user')
+                = 'user_01h455vb4pex5vsknk084sn02q'::typeid",
+            // ordered by prefix, then by UUID
+            "'user_01h455vb4pex5vsknk084sn02q'::typeid < 'user_01h455vb4pex5vsknk084sn02r'",
+            "'user_7zzzzzzzzzzzzzzzzzzzzzzzzz'::typeid < 'user_admin_00000000000000000000000000'",
+            "'7zzzzzzzzzzzzzzzzzzzzzzzzz'::typeid < 'a_00000000000000000000000000'",
+        ]);
+
+        let invalid = Some(PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION);
+        for text in [
+            "PREFIX_00000000000000000000000000",
+            "_00000000000000000000000000",
+            "_prefix_00000000000000000000000000",
+            "prefix__00000000000000000000000000",
+            "pre.fix_00000000000000000000000000",
+            "prefix_0000000000000000000000000",
+            "prefix_000000000000000000000000000",
+            "prefix_0000000000000000000000000o",
+            "prefix_0000000000000000000000000U",
+            "prefix_80000000000000000000000000",
+            "prefix_",
+            "",
+        ] {
+            assert_eq!(
+                error_code(&format!("SELECT '{text}'::typeid")),
+                invalid,
+                "{text}"
+            );
+        }
+        let long_prefix = "a".repeat(64);
+        assert_eq!(
+            error_code(&format!(
+                "SELECT '{long_prefix}_00000000000000000000000000'::typeid"
+            )),
+            invalid
+        );
+        assert_eq!(
+            error_code("SELECT typeid_parse('user_01h455vb4pex5vsknk084sn02q', 'org')"),
+            invalid
+        );
+        assert_eq!(
+            error_code("SELECT typeid_generate('User')"),
+            Some(PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE)
+        );
+        // typeid() takes a UUIDv7 only
+        for uuid in [
+            "gen_random_uuid()",
+            "uuid_nil()",
+            "'c232ab00-9414-11ec-b3c8-9f6bdeced846'",
+        ] {
+            assert_eq!(
+                error_code(&format!("SELECT typeid('user', {uuid})")),
+                Some(PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE),
+                "{uuid}"
+            );
+        }
+
+        // the prefix is checked at insert time with a domain
+        Spi::run(
+            "
+            CREATE DOMAIN user_id AS typeid CHECK (typeid_prefix(VALUE) = 'user');
+            CREATE TABLE users (id user_id PRIMARY KEY DEFAULT typeid_generate('user'));
+            INSERT INTO users DEFAULT VALUES;
+            INSERT INTO users VALUES ('user_01h455vb4pex5vsknk084sn02q');
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            error_code("INSERT INTO users VALUES ('org_01h455vb4pex5vsknk084sn02q')"),
+            Some(PgSqlErrorCode::ERRCODE_CHECK_VIOLATION)
+        );
+        let found = Spi::get_one::<i64>(
+            "
+            SELECT count(*) FROM users
+            WHERE id = 'user_01h455vb4pex5vsknk084sn02q' AND id::uuid = '01890a5d-ac96-774b-bcce-b302099a8057';
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(found, 1);
+    }
+
+    #[cfg(any(feature = "pg17", feature = "pg18"))]
+    #[pg_test]
+    fn test_uuid_to_timestamptz_matches_native() {
+        let mismatches = Spi::get_one::<i64>(
+            "
+            SELECT count(*)
+            FROM generate_series(0, 9999) AS n,
+                LATERAL (SELECT '2012-03-04T05:06:07+00:00'::timestamptz
+                    + make_interval(secs => n * 0.123457) AS ts) AS t,
+                LATERAL (VALUES (uuid_generate_v7(ts)), (uuid_generate_v1(ts))) AS u(id)
+            WHERE uuid_to_timestamptz(id) IS DISTINCT FROM uuid_extract_timestamp(id);
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(mismatches, 0);
+
+        // ticks that are not whole microseconds are truncated, as by uuid_extract_timestamp
+        for time_low in ["c232ab05", "c232ab07", "c232ab08", "c232ab09"] {
+            let v1 = format!("'{time_low}-9414-11ec-b3c8-9f6bdeced846'::uuid");
+            assert_sql(&[
+                &format!("uuid_to_timestamptz({v1}) = uuid_extract_timestamp({v1})"),
+                &format!("uuid_to_timestamptz(uuid_v1_to_v6({v1})) = uuid_extract_timestamp({v1})"),
+                &format!("uuid_to_timestamptz({v1}) = '2022-02-22T19:22:22+00:00'"),
+            ]);
+        }
+    }
+
+    #[pg_test]
+    fn test_uuid_is_valid() {
+        assert_sql(&[
+            "uuid_is_valid_v1('c232ab00-9414-11ec-b3c8-9f6bdeced846')",
+            "uuid_is_valid_v3(uuid_generate_v3(uuid_ns_dns(), 'python.org'))",
+            "uuid_is_valid_v4(gen_random_uuid())",
+            "uuid_is_valid_v5(uuid_generate_v5(uuid_ns_dns(), 'python.org'))",
+            "uuid_is_valid_v6('1ec9414c-232a-6b00-b3c8-9f6bdeced846')",
+            "uuid_is_valid_v7('017f22e2-79b0-7cc3-98c4-dc0c0c07398f')",
+            "uuid_is_valid_v8('2489e9ad-2ee2-8e00-8ec9-32d5f69181c0')",
+            "NOT uuid_is_valid_v7('c232ab00-9414-11ec-b3c8-9f6bdeced846')",
+            // version 7 with the NCS, Microsoft and future variants
+            "NOT uuid_is_valid_v7('017f22e2-79b0-7cc3-18c4-dc0c0c07398f')",
+            "NOT uuid_is_valid_v7('017f22e2-79b0-7cc3-c8c4-dc0c0c07398f')",
+            "NOT uuid_is_valid_v7('017f22e2-79b0-7cc3-e8c4-dc0c0c07398f')",
+            "uuid_is_valid_v7(NULL) IS NULL",
+        ]);
+    }
+
+    #[pg_test]
+    fn test_uuidv7_type() {
+        fn try_select(sql: &str) -> bool {
+            PgTryBuilder::new(|| matches!(Spi::run(sql), Ok(())))
+                .catch_others(|_| false)
+                .catch_rust_panic(|_| false)
+                .execute()
+        }
+
+        // a base type with the same representation as uuid
+        let (typlen, typbyval) = Spi::get_two::<i16, bool>(
+            "SELECT typlen, typbyval FROM pg_type WHERE typname = 'uuidv7' AND typtype = 'b';",
+        )
+        .unwrap();
+        assert_eq!(typlen, Some(16));
+        assert_eq!(typbyval, Some(false));
+
+        // the version is checked at input and on cast from uuid
+        assert!(try_select(
+            "SELECT '01890a5d-ac96-774b-bcce-b302099a8057'::uuidv7;"
+        ));
+        assert!(!try_select(
+            "SELECT '0a7a9c7b-0c55-4c8b-9f50-6d1e3c0f7e21'::uuidv7;"
+        ));
+        assert!(try_select("SELECT uuid_generate_v7_now()::uuidv7;"));
+        assert!(!try_select("SELECT gen_random_uuid()::uuidv7;"));
+        // the variant is checked too
+        assert!(!try_select(
+            "SELECT '01890a5d-ac96-774b-1cce-b302099a8057'::uuidv7;"
+        ));
+
+        // binary representation is the one of uuid
+        let same_binary = Spi::get_one::<bool>(
+            "
+            SELECT uuidv7_send(v) = uuid_send(v::uuid)
+                AND v::uuid = '01890a5d-ac96-774b-bcce-b302099a8057'::uuid
+            FROM (SELECT '01890a5d-ac96-774b-bcce-b302099a8057'::uuidv7 AS v) t;
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert!(same_binary);
+
+        // btree and hash opclasses
+        Spi::run(
+            "
+            CREATE TEMP TABLE uuidv7_type_test (id uuidv7 PRIMARY KEY, n int);
+            CREATE INDEX ON uuidv7_type_test USING hash (id);
+            INSERT INTO uuidv7_type_test
+            SELECT uuid_generate_v7(
+                '2012-03-04T05:06:07+00:00'::timestamptz + n * INTERVAL '1 second'
+            ), n
+            FROM generate_series(1, 100) AS n;
+            ",
+        )
+        .unwrap();
+        let in_order = Spi::get_one::<bool>(
+            "
+            SELECT bool_and(n = rn) FROM (
+                SELECT n, row_number() OVER (ORDER BY id) AS rn FROM uuidv7_type_test
+            ) t;
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert!(in_order);
+        let found = Spi::get_one::<i32>(
+            "
+            SELECT n FROM uuidv7_type_test
+            WHERE id = (SELECT id FROM uuidv7_type_test WHERE n = 42);
+            ",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(found, 42);
+    }
+
     #[cfg(not(any(feature = "pg17", feature = "pg18")))]
     #[pg_test]
     fn test_uuid_extract_timestamp_pg16_only() {
@@ -566,6 +1948,6 @@ pub mod pg_test {
 
     pub fn postgresql_conf_options() -> Vec<&'static str> {
         // return any postgresql.conf settings that are required for your tests
-        vec![]
+        vec!["shared_preload_libraries = 'pgx_uuidv7'"]
     }
 }
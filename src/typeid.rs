@@ -0,0 +1,277 @@
+use crate::my_converter::Converter;
+use crate::ulid;
+use pgrx::prelude::*;
+use std::ffi::CStr;
+
+/// Maximum length of a TypeID prefix.
+const PREFIX_MAX_LEN: usize = 63;
+
+/// Check a TypeID prefix: at most 63 lower case letters and underscores,
+/// starting and ending with a letter, or empty.
+fn check_prefix(prefix: &str) -> Result<(), &'static str> {
+    if prefix.len() > PREFIX_MAX_LEN {
+        return Err("A TypeID prefix is at most 63 characters long.");
+    }
+    if !prefix.bytes().all(|c| c.is_ascii_lowercase() || c == b'_') {
+        return Err("A TypeID prefix only contains lower case letters and underscores.");
+    }
+    if prefix.starts_with('_') || prefix.ends_with('_') {
+        return Err("A TypeID prefix starts and ends with a letter.");
+    }
+    Ok(())
+}
+
+/// Decode the suffix of a TypeID, 26 digits of lower case Crockford's Base32.
+fn decode_suffix(suffix: &str) -> Result<u128, &'static str> {
+    const DETAIL: &str =
+        "A TypeID suffix is 26 characters of lower case Crockford's Base32, and the first one is at most 7.";
+    let alphabet = |c: u8| matches!(c, b'0'..=b'9' | b'a'..=b'z') && !b"ilou".contains(&c);
+    if !suffix.bytes().all(alphabet) {
+        return Err(DETAIL);
+    }
+    ulid::decode(suffix).ok_or(DETAIL)
+}
+
+/// Parse a TypeID into its prefix and the bits of its UUID.
+/// The text is split at the last underscore, and there is none when the prefix is empty.
+fn parse(s: &str) -> Result<(&str, u128), &'static str> {
+    let (prefix, suffix) = match s.rsplit_once('_') {
+        Some(("", _)) => return Err("A TypeID with an empty prefix has no separator."),
+        Some(parts) => parts,
+        None => ("", s),
+    };
+    check_prefix(prefix)?;
+    Ok((prefix, decode_suffix(suffix)?))
+}
+
+/// Return the TypeID text of a prefix, which must be valid, and a UUID.
+fn to_text(prefix: &str, uuid: pgrx::Uuid) -> String {
+    let u: uuid::Uuid = Converter(uuid).into();
+    let suffix = ulid::encode(u.as_u128()).to_ascii_lowercase();
+    if prefix.is_empty() {
+        suffix
+    } else {
+        format!("{prefix}_{suffix}")
+    }
+}
+
+/// Raise an error unless the given text is a valid TypeID.
+fn check_typeid(s: &str, errcode: PgSqlErrorCode) -> String {
+    if let Err(detail) = parse(s) {
+        ereport!(
+            ERROR,
+            errcode,
+            format!("invalid input syntax for type typeid: \"{s}\""),
+            detail
+        );
+    }
+    s.to_owned()
+}
+
+/// Raise an error unless the given prefix is a valid TypeID prefix.
+fn check_prefix_param(prefix: &str) {
+    if let Err(detail) = check_prefix(prefix) {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            format!("invalid TypeID prefix: \"{prefix}\""),
+            detail
+        );
+    }
+}
+
+/// Input function of the typeid type.
+/// Accepts `prefix_suffix`, or only the suffix for an empty prefix, as in the TypeID spec.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn typeid_in(input: &CStr) -> String {
+    let s = input.to_string_lossy();
+    check_typeid(&s, PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION)
+}
+
+/// Binary input function of the typeid type.
+/// Accepts the same format as text and checks it like the input function.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn typeid_recv(buf: pgrx::datum::Internal) -> String {
+    let s: String =
+        unsafe { pgrx::direct_function_call(pg_sys::textrecv, &[buf.unwrap()]) }.unwrap();
+    check_typeid(&s, PgSqlErrorCode::ERRCODE_INVALID_BINARY_REPRESENTATION)
+}
+
+/// Build a TypeID from a prefix and a UUIDv7.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn typeid(prefix: &str, uuid: pgrx::Uuid) -> String {
+    check_prefix_param(prefix);
+    crate::check_version(Converter(uuid).into(), 7);
+    to_text(prefix, uuid)
+}
+
+/// Generate a new TypeID with the given prefix and a UUIDv7 for the current time.
+#[pg_extern(parallel_safe, sql = false)]
+fn typeid_generate(prefix: &str) -> String {
+    check_prefix_param(prefix);
+    to_text(prefix, crate::uuid_generate_v7_now())
+}
+
+/// Parse a TypeID, raising an error unless its prefix is the expected one.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn typeid_parse(input: &str, prefix: &str) -> String {
+    let s = check_typeid(input, PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION);
+    let actual = parse(&s).map(|(p, _)| p).unwrap_or_default();
+    if actual != prefix {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION,
+            format!("unexpected TypeID prefix: \"{input}\""),
+            format!("The prefix is \"{actual}\", expected \"{prefix}\".")
+        );
+    }
+    s
+}
+
+/// Return the prefix of a TypeID, which is empty if it has none.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn typeid_prefix(id: &str) -> &str {
+    id.rsplit_once('_').map(|(prefix, _)| prefix).unwrap_or("")
+}
+
+/// Return the UUID of a TypeID.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn typeid_to_uuid(id: &str) -> pgrx::Uuid {
+    // typeid values are checked on input, so the suffix is valid.
+    let (_, v) = parse(id).unwrap();
+    Converter(uuid::Uuid::from_u128(v)).into()
+}
+
+// typeid is stored as its canonical text. Every character is ASCII and the suffix
+// sorts like the UUID, so comparing the bytes orders by prefix and then by UUID,
+// and everything but the input is delegated to the built-in text and bytea functions.
+extension_sql!(
+    r#"
+CREATE TYPE typeid;
+
+CREATE FUNCTION typeid_in(cstring) RETURNS typeid
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'typeid_in_wrapper';
+CREATE FUNCTION typeid_out(typeid) RETURNS cstring
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE internal AS 'textout';
+CREATE FUNCTION typeid_recv(internal) RETURNS typeid
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'typeid_recv_wrapper';
+CREATE FUNCTION typeid_send(typeid) RETURNS bytea
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE internal AS 'textsend';
+
+CREATE TYPE typeid (
+    INPUT = typeid_in,
+    OUTPUT = typeid_out,
+    RECEIVE = typeid_recv,
+    SEND = typeid_send,
+    LIKE = text
+);
+
+COMMENT ON TYPE typeid IS 'A TypeID, a type prefix and a UUID, such as user_01h455vb4pex5vsknk084sn02q.';
+
+-- comparison
+CREATE FUNCTION typeid_lt(typeid, typeid) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'bytealt';
+CREATE FUNCTION typeid_le(typeid, typeid) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'byteale';
+CREATE FUNCTION typeid_eq(typeid, typeid) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'byteaeq';
+CREATE FUNCTION typeid_ne(typeid, typeid) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'byteane';
+CREATE FUNCTION typeid_ge(typeid, typeid) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'byteage';
+CREATE FUNCTION typeid_gt(typeid, typeid) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'byteagt';
+CREATE FUNCTION typeid_cmp(typeid, typeid) RETURNS int4
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'byteacmp';
+CREATE FUNCTION typeid_sortsupport(internal) RETURNS void
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE internal AS 'bytea_sortsupport';
+CREATE FUNCTION typeid_hash(typeid) RETURNS int4
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'hashvarlena';
+CREATE FUNCTION typeid_hash_extended(typeid, int8) RETURNS int8
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'hashvarlenaextended';
+
+CREATE OPERATOR < (
+    LEFTARG = typeid, RIGHTARG = typeid, FUNCTION = typeid_lt,
+    COMMUTATOR = >, NEGATOR = >=,
+    RESTRICT = scalarltsel, JOIN = scalarltjoinsel
+);
+CREATE OPERATOR <= (
+    LEFTARG = typeid, RIGHTARG = typeid, FUNCTION = typeid_le,
+    COMMUTATOR = >=, NEGATOR = >,
+    RESTRICT = scalarlesel, JOIN = scalarlejoinsel
+);
+CREATE OPERATOR = (
+    LEFTARG = typeid, RIGHTARG = typeid, FUNCTION = typeid_eq,
+    COMMUTATOR = =, NEGATOR = <>,
+    RESTRICT = eqsel, JOIN = eqjoinsel, HASHES, MERGES
+);
+CREATE OPERATOR <> (
+    LEFTARG = typeid, RIGHTARG = typeid, FUNCTION = typeid_ne,
+    COMMUTATOR = <>, NEGATOR = =,
+    RESTRICT = neqsel, JOIN = neqjoinsel
+);
+CREATE OPERATOR >= (
+    LEFTARG = typeid, RIGHTARG = typeid, FUNCTION = typeid_ge,
+    COMMUTATOR = <=, NEGATOR = <,
+    RESTRICT = scalargesel, JOIN = scalargejoinsel
+);
+CREATE OPERATOR > (
+    LEFTARG = typeid, RIGHTARG = typeid, FUNCTION = typeid_gt,
+    COMMUTATOR = <, NEGATOR = <=,
+    RESTRICT = scalargtsel, JOIN = scalargtjoinsel
+);
+
+CREATE OPERATOR CLASS typeid_ops
+    DEFAULT FOR TYPE typeid USING btree AS
+        OPERATOR 1 <,
+        OPERATOR 2 <=,
+        OPERATOR 3 =,
+        OPERATOR 4 >=,
+        OPERATOR 5 >,
+        FUNCTION 1 typeid_cmp(typeid, typeid),
+        FUNCTION 2 typeid_sortsupport(internal),
+        FUNCTION 4 btequalimage(oid);
+
+CREATE OPERATOR CLASS typeid_ops
+    DEFAULT FOR TYPE typeid USING hash AS
+        OPERATOR 1 =,
+        FUNCTION 1 typeid_hash(typeid),
+        FUNCTION 2 typeid_hash_extended(typeid, int8);
+
+-- functions
+CREATE FUNCTION typeid(prefix text, uuid uuid) RETURNS typeid
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'typeid_wrapper';
+
+COMMENT ON FUNCTION "typeid"(text, uuid)
+IS 'Build a TypeID from a prefix and a UUIDv7, raising an error for another version. The prefix is at most 63 lower case letters and underscores, starting and ending with a letter, or empty.';
+
+CREATE FUNCTION typeid_generate(prefix text) RETURNS typeid
+    VOLATILE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'typeid_generate_wrapper';
+
+COMMENT ON FUNCTION "typeid_generate"(text)
+IS 'Generate a new TypeID with the given prefix and a UUIDv7 for the current time.';
+
+CREATE FUNCTION typeid_parse(input text, prefix text) RETURNS typeid
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'typeid_parse_wrapper';
+
+COMMENT ON FUNCTION "typeid_parse"(text, text)
+IS 'Parse a TypeID, raising an error unless its prefix is the expected one.';
+
+CREATE FUNCTION typeid_prefix(typeid) RETURNS text
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'typeid_prefix_wrapper';
+
+COMMENT ON FUNCTION "typeid_prefix"(typeid)
+IS 'Return the prefix of a TypeID, which is empty if it has none.';
+
+CREATE FUNCTION typeid_to_uuid(typeid) RETURNS uuid
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'typeid_to_uuid_wrapper';
+
+COMMENT ON FUNCTION "typeid_to_uuid"(typeid)
+IS 'Return the UUID of a TypeID.';
+
+-- casts
+-- The prefix is lost, so the cast to uuid is not implicit.
+CREATE CAST (typeid AS uuid) WITH FUNCTION typeid_to_uuid(typeid) AS ASSIGNMENT;
+"#,
+    name = "type_typeid",
+);
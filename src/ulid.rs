@@ -0,0 +1,85 @@
+use crate::my_converter::Converter;
+use pgrx::prelude::*;
+
+/// Crockford's Base32 alphabet, as used by ULID.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Length of a ULID in text form, 128 bits in 5-bit digits.
+const ULID_LEN: usize = 26;
+
+/// Return the value of a Crockford's Base32 digit.
+/// Lower case is accepted, and so are `I` and `L` for 1 and `O` for 0.
+fn decode_digit(c: u8) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        c @ b'0'..=b'9' => Some(c - b'0'),
+        b'O' => Some(0),
+        b'I' | b'L' => Some(1),
+        c => ALPHABET.iter().position(|&a| a == c).map(|i| i as u8),
+    }
+}
+
+/// Encode 128 bits as a ULID, with the most significant bits first.
+pub(crate) fn encode(v: u128) -> String {
+    (0..ULID_LEN)
+        .rev()
+        .map(|i| ALPHABET[((v >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// Decode a ULID to 128 bits.
+/// Return None if it is not 26 digits long, or if it overflows 128 bits.
+pub(crate) fn decode(s: &str) -> Option<u128> {
+    if s.len() != ULID_LEN {
+        return None;
+    }
+    // 26 digits hold 130 bits, so the first digit can be at most 7.
+    s.bytes().enumerate().try_fold(0u128, |acc, (i, c)| {
+        let d = decode_digit(c)?;
+        if i == 0 && d > 7 {
+            return None;
+        }
+        Some(acc << 5 | d as u128)
+    })
+}
+
+/// Convert a UUID to its 26-character ULID text.
+/// The bits are the same, so the 48-bit timestamp of a UUIDv7 is the ULID timestamp.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_to_ulid_text(uuid: pgrx::Uuid) -> String {
+    let u: uuid::Uuid = Converter(uuid).into();
+    encode(u.as_u128())
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_to_ulid_text"(uuid)
+IS 'Convert a UUID to its 26-character ULID text. The bits are the same, so the 48-bit timestamp of a UUIDv7 is the ULID timestamp.';
+"#,
+    name = "comment_uuid_to_ulid_text",
+    requires = [uuid_to_ulid_text],
+);
+
+/// Convert a 26-character ULID text to a UUID with the same bits.
+/// Lower case is accepted, and so are `I` and `L` for 1 and `O` for 0.
+#[pg_extern(immutable, parallel_safe)]
+fn ulid_text_to_uuid(ulid: &str) -> pgrx::Uuid {
+    let v = decode(ulid);
+    if v.is_none() {
+        ereport!(
+            ERROR,
+            PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION,
+            format!("invalid input syntax for ULID: \"{ulid}\""),
+            "A ULID is 26 characters of Crockford's Base32, and the first one is at most 7."
+        );
+    }
+    Converter(uuid::Uuid::from_u128(v.unwrap())).into()
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "ulid_text_to_uuid"(text)
+IS 'Convert a 26-character ULID text to a UUID with the same bits. Lower case is accepted, and so are I and L for 1 and O for 0.';
+"#,
+    name = "comment_ulid_text_to_uuid",
+    requires = [ulid_text_to_uuid],
+);
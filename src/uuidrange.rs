@@ -0,0 +1,60 @@
+use crate::my_converter::Converter;
+use pgrx::prelude::*;
+
+/// Return the difference between two UUIDs in milliseconds of the UUIDv7 timestamp.
+/// The bits after the timestamp give the fraction, so the result grows with the UUID
+/// for every version. This is the subtype difference of uuidrange.
+#[pg_extern(immutable, parallel_safe)]
+fn uuid_subtype_diff(a: pgrx::Uuid, b: pgrx::Uuid) -> f64 {
+    let a: uuid::Uuid = Converter(a).into();
+    let b: uuid::Uuid = Converter(b).into();
+    // subtract exactly before converting, so that close UUIDs do not round to the same f64
+    let d = a.as_u128().abs_diff(b.as_u128()) as f64;
+    let d = if a < b { -d } else { d };
+    // 128 bits minus the 48-bit timestamp
+    let scale = (1u128 << 80) as f64;
+    d / scale
+}
+
+extension_sql!(
+    r#"
+COMMENT ON FUNCTION "uuid_subtype_diff"(uuid, uuid)
+IS 'Return the difference between two UUIDs in milliseconds of the UUIDv7 timestamp. The bits after the timestamp give the fraction. This is the subtype difference of uuidrange.';
+
+CREATE TYPE uuidrange AS RANGE (
+    SUBTYPE = uuid,
+    SUBTYPE_DIFF = uuid_subtype_diff
+);
+
+COMMENT ON TYPE uuidrange IS 'A range of UUIDs.';
+
+CREATE FUNCTION tstzrange_to_uuidrange(r tstzrange) RETURNS uuidrange
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$
+    SELECT CASE
+        WHEN isempty(r) THEN 'empty'::uuidrange
+        -- no whole millisecond in the range
+        WHEN b.lower > b.upper THEN 'empty'::uuidrange
+        ELSE uuidrange(
+            CASE WHEN lower_inf(r) THEN NULL ELSE b.lower END,
+            CASE WHEN upper_inf(r) THEN NULL ELSE b.upper END,
+            '[]'
+        )
+    END
+    FROM tstzrange_to_uuid_range(r) AS b
+    $$;
+
+COMMENT ON FUNCTION "tstzrange_to_uuidrange"(tstzrange)
+IS 'Return the range of UUIDv7 whose timestamps are in the given range, at millisecond precision. A range that holds no whole millisecond gives an empty range.';
+
+CREATE FUNCTION timestamptz_to_uuidrange(lower timestamptz, upper timestamptz, bounds text DEFAULT '[)')
+    RETURNS uuidrange
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    RETURN tstzrange_to_uuidrange(tstzrange(lower, upper, bounds));
+
+COMMENT ON FUNCTION "timestamptz_to_uuidrange"(timestamptz, timestamptz, text)
+IS 'Return the range of UUIDv7 whose timestamps are between the given timestamps, at millisecond precision.';
+"#,
+    name = "type_uuidrange",
+    requires = [uuid_subtype_diff, "tstzrange_to_uuid_range"],
+);
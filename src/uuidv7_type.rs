@@ -0,0 +1,331 @@
+use crate::is_valid_version;
+use crate::my_converter::Converter;
+use pgrx::prelude::*;
+use std::ffi::CStr;
+
+/// Raise an error unless the given uuid is a version 7 UUID with the RFC 9562 variant.
+fn check_uuidv7(uuid: pgrx::Uuid, errcode: PgSqlErrorCode) -> pgrx::Uuid {
+    let u: uuid::Uuid = Converter(uuid).into();
+    if !is_valid_version(&u, 7) {
+        let detail = match u.get_version_num() {
+            7 => format!("UUID variant is {:?}, expected RFC 9562.", u.get_variant()),
+            v => format!("UUID version is {v}, expected 7."),
+        };
+        ereport!(
+            ERROR,
+            errcode,
+            format!("invalid value for type uuidv7: \"{u}\""),
+            detail
+        );
+    }
+    uuid
+}
+
+/// Input function of the uuidv7 type.
+/// Accepts the same formats as uuid and checks the version and the variant.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn uuidv7_in(input: &CStr) -> pgrx::Uuid {
+    let uuid: pgrx::Uuid =
+        unsafe { pgrx::direct_function_call(pg_sys::uuid_in, &[input.into_datum()]) }.unwrap();
+    check_uuidv7(uuid, PgSqlErrorCode::ERRCODE_INVALID_TEXT_REPRESENTATION)
+}
+
+/// Binary input function of the uuidv7 type.
+/// Accepts the same format as uuid and checks the version and the variant.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn uuidv7_recv(buf: pgrx::datum::Internal) -> pgrx::Uuid {
+    let uuid: pgrx::Uuid =
+        unsafe { pgrx::direct_function_call(pg_sys::uuid_recv, &[buf.unwrap()]) }.unwrap();
+    check_uuidv7(uuid, PgSqlErrorCode::ERRCODE_INVALID_BINARY_REPRESENTATION)
+}
+
+/// Convert a uuid to a uuidv7, checking the version and the variant.
+#[pg_extern(immutable, parallel_safe, sql = false)]
+fn uuid_to_uuidv7(uuid: pgrx::Uuid) -> pgrx::Uuid {
+    check_uuidv7(uuid, PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE)
+}
+
+// uuidv7 has the same binary representation as uuid,
+// so everything but the version check is delegated to the built-in uuid functions.
+extension_sql!(
+    r#"
+CREATE TYPE uuidv7;
+
+CREATE FUNCTION uuidv7_in(cstring) RETURNS uuidv7
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'uuidv7_in_wrapper';
+CREATE FUNCTION uuidv7_out(uuidv7) RETURNS cstring
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE internal AS 'uuid_out';
+CREATE FUNCTION uuidv7_recv(internal) RETURNS uuidv7
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'uuidv7_recv_wrapper';
+CREATE FUNCTION uuidv7_send(uuidv7) RETURNS bytea
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE internal AS 'uuid_send';
+
+CREATE TYPE uuidv7 (
+    INPUT = uuidv7_in,
+    OUTPUT = uuidv7_out,
+    RECEIVE = uuidv7_recv,
+    SEND = uuidv7_send,
+    LIKE = uuid
+);
+
+COMMENT ON TYPE uuidv7 IS 'A UUID that is specifically version 7.';
+
+-- comparison
+CREATE FUNCTION uuidv7_lt(uuidv7, uuidv7) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_lt';
+CREATE FUNCTION uuidv7_le(uuidv7, uuidv7) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_le';
+CREATE FUNCTION uuidv7_eq(uuidv7, uuidv7) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_eq';
+CREATE FUNCTION uuidv7_ne(uuidv7, uuidv7) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_ne';
+CREATE FUNCTION uuidv7_ge(uuidv7, uuidv7) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_ge';
+CREATE FUNCTION uuidv7_gt(uuidv7, uuidv7) RETURNS bool
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_gt';
+CREATE FUNCTION uuidv7_cmp(uuidv7, uuidv7) RETURNS int4
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_cmp';
+CREATE FUNCTION uuidv7_sortsupport(internal) RETURNS void
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE internal AS 'uuid_sortsupport';
+CREATE FUNCTION uuidv7_hash(uuidv7) RETURNS int4
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_hash';
+CREATE FUNCTION uuidv7_hash_extended(uuidv7, int8) RETURNS int8
+    IMMUTABLE STRICT PARALLEL SAFE LEAKPROOF LANGUAGE internal AS 'uuid_hash_extended';
+
+CREATE OPERATOR < (
+    LEFTARG = uuidv7, RIGHTARG = uuidv7, FUNCTION = uuidv7_lt,
+    COMMUTATOR = >, NEGATOR = >=,
+    RESTRICT = scalarltsel, JOIN = scalarltjoinsel
+);
+CREATE OPERATOR <= (
+    LEFTARG = uuidv7, RIGHTARG = uuidv7, FUNCTION = uuidv7_le,
+    COMMUTATOR = >=, NEGATOR = >,
+    RESTRICT = scalarlesel, JOIN = scalarlejoinsel
+);
+CREATE OPERATOR = (
+    LEFTARG = uuidv7, RIGHTARG = uuidv7, FUNCTION = uuidv7_eq,
+    COMMUTATOR = =, NEGATOR = <>,
+    RESTRICT = eqsel, JOIN = eqjoinsel, HASHES, MERGES
+);
+CREATE OPERATOR <> (
+    LEFTARG = uuidv7, RIGHTARG = uuidv7, FUNCTION = uuidv7_ne,
+    COMMUTATOR = <>, NEGATOR = =,
+    RESTRICT = neqsel, JOIN = neqjoinsel
+);
+CREATE OPERATOR >= (
+    LEFTARG = uuidv7, RIGHTARG = uuidv7, FUNCTION = uuidv7_ge,
+    COMMUTATOR = <=, NEGATOR = <,
+    RESTRICT = scalargesel, JOIN = scalargejoinsel
+);
+CREATE OPERATOR > (
+    LEFTARG = uuidv7, RIGHTARG = uuidv7, FUNCTION = uuidv7_gt,
+    COMMUTATOR = <, NEGATOR = <=,
+    RESTRICT = scalargtsel, JOIN = scalargtjoinsel
+);
+
+CREATE OPERATOR CLASS uuidv7_ops
+    DEFAULT FOR TYPE uuidv7 USING btree AS
+        OPERATOR 1 <,
+        OPERATOR 2 <=,
+        OPERATOR 3 =,
+        OPERATOR 4 >=,
+        OPERATOR 5 >,
+        FUNCTION 1 uuidv7_cmp(uuidv7, uuidv7),
+        FUNCTION 2 uuidv7_sortsupport(internal),
+        FUNCTION 4 btequalimage(oid);
+
+CREATE OPERATOR CLASS uuidv7_ops
+    DEFAULT FOR TYPE uuidv7 USING hash AS
+        OPERATOR 1 =,
+        FUNCTION 1 uuidv7_hash(uuidv7),
+        FUNCTION 2 uuidv7_hash_extended(uuidv7, int8);
+
+-- BRIN opclasses, same as the built-in ones for uuid.
+-- UUIDv7 values grow with time, so on append-only tables minmax summaries stay narrow.
+CREATE OPERATOR CLASS uuidv7_minmax_ops
+    DEFAULT FOR TYPE uuidv7 USING brin AS
+        OPERATOR 1 <,
+        OPERATOR 2 <=,
+        OPERATOR 3 =,
+        OPERATOR 4 >=,
+        OPERATOR 5 >,
+        FUNCTION 1 brin_minmax_opcinfo(internal),
+        FUNCTION 2 brin_minmax_add_value(internal, internal, internal, internal),
+        FUNCTION 3 brin_minmax_consistent(internal, internal, internal),
+        FUNCTION 4 brin_minmax_union(internal, internal, internal);
+
+CREATE OPERATOR CLASS uuidv7_minmax_multi_ops
+    FOR TYPE uuidv7 USING brin AS
+        OPERATOR 1 <,
+        OPERATOR 2 <=,
+        OPERATOR 3 =,
+        OPERATOR 4 >=,
+        OPERATOR 5 >,
+        FUNCTION 1 brin_minmax_multi_opcinfo(internal),
+        FUNCTION 2 brin_minmax_multi_add_value(internal, internal, internal, internal),
+        FUNCTION 3 brin_minmax_multi_consistent(internal, internal, internal, int4),
+        FUNCTION 4 brin_minmax_multi_union(internal, internal, internal),
+        FUNCTION 5 brin_minmax_multi_options(internal),
+        FUNCTION 11 brin_minmax_multi_distance_uuid(internal, internal);
+
+CREATE OPERATOR CLASS uuidv7_bloom_ops
+    FOR TYPE uuidv7 USING brin AS
+        OPERATOR 1 =,
+        FUNCTION 1 brin_bloom_opcinfo(internal),
+        FUNCTION 2 brin_bloom_add_value(internal, internal, internal, internal),
+        FUNCTION 3 brin_bloom_consistent(internal, internal, internal, int4),
+        FUNCTION 4 brin_bloom_union(internal, internal, internal),
+        FUNCTION 5 brin_bloom_options(internal),
+        FUNCTION 11 uuidv7_hash(uuidv7);
+
+-- casts
+CREATE FUNCTION uuid_to_uuidv7(uuid) RETURNS uuidv7
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c AS 'MODULE_PATHNAME', 'uuid_to_uuidv7_wrapper';
+
+COMMENT ON FUNCTION "uuid_to_uuidv7"(uuid)
+IS 'Convert a uuid to a uuidv7. Raise an error if the uuid is not version 7 with the RFC 9562 variant.';
+
+CREATE CAST (uuidv7 AS uuid) WITHOUT FUNCTION AS IMPLICIT;
+-- uuid to uuidv7 checks the version, so that every uuidv7 is validated exactly once.
+CREATE CAST (uuid AS uuidv7) WITH FUNCTION uuid_to_uuidv7(uuid) AS ASSIGNMENT;
+"#,
+    name = "type_uuidv7",
+);
+
+// Same as uuid_timestamptz_operators, for uuidv7 columns and their uuidv7_ops indexes.
+// The clamped bounds are returned as uuidv7 without the version check,
+// because the nil and max UUIDs of out-of-range timestamps are not valid uuidv7 values.
+// They only serve as bounds, since the comparisons are bytewise like those of uuid.
+extension_sql!(
+    r#"
+CREATE FUNCTION timestamptz_to_uuidv7_min_clamped(timestamptz) RETURNS uuidv7
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c
+    AS 'MODULE_PATHNAME', 'timestamptz_to_uuid_v7_min_clamped_wrapper';
+
+COMMENT ON FUNCTION "timestamptz_to_uuidv7_min_clamped"(timestamptz)
+IS 'Same as timestamptz_to_uuid_v7_min_clamped, as a uuidv7 bound for comparisons. Out of the range of UUIDv7, the result is the nil or the max UUID, which are not valid uuidv7 values.';
+
+CREATE FUNCTION timestamptz_to_uuidv7_max_clamped(timestamptz) RETURNS uuidv7
+    IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c
+    AS 'MODULE_PATHNAME', 'timestamptz_to_uuid_v7_max_clamped_wrapper';
+
+COMMENT ON FUNCTION "timestamptz_to_uuidv7_max_clamped"(timestamptz)
+IS 'Same as timestamptz_to_uuid_v7_max_clamped, as a uuidv7 bound for comparisons. Out of the range of UUIDv7, the result is the nil or the max UUID, which are not valid uuidv7 values.';
+
+CREATE FUNCTION uuidv7_lt_timestamptz(u uuidv7, ts timestamptz) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u < timestamptz_to_uuidv7_min_clamped(ts);
+
+CREATE FUNCTION uuidv7_le_timestamptz(u uuidv7, ts timestamptz) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u <= timestamptz_to_uuidv7_max_clamped(ts);
+
+CREATE FUNCTION uuidv7_gt_timestamptz(u uuidv7, ts timestamptz) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u > timestamptz_to_uuidv7_max_clamped(ts);
+
+CREATE FUNCTION uuidv7_ge_timestamptz(u uuidv7, ts timestamptz) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u >= timestamptz_to_uuidv7_min_clamped(ts);
+
+CREATE FUNCTION timestamptz_lt_uuidv7(ts timestamptz, u uuidv7) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u > timestamptz_to_uuidv7_max_clamped(ts);
+
+CREATE FUNCTION timestamptz_le_uuidv7(ts timestamptz, u uuidv7) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u >= timestamptz_to_uuidv7_min_clamped(ts);
+
+CREATE FUNCTION timestamptz_gt_uuidv7(ts timestamptz, u uuidv7) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u < timestamptz_to_uuidv7_min_clamped(ts);
+
+CREATE FUNCTION timestamptz_ge_uuidv7(ts timestamptz, u uuidv7) RETURNS bool
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    RETURN u <= timestamptz_to_uuidv7_max_clamped(ts);
+
+CREATE OPERATOR < (
+    LEFTARG = uuidv7, RIGHTARG = timestamptz, FUNCTION = uuidv7_lt_timestamptz,
+    COMMUTATOR = >, NEGATOR = >=,
+    RESTRICT = scalarltsel, JOIN = scalarltjoinsel
+);
+
+CREATE OPERATOR <= (
+    LEFTARG = uuidv7, RIGHTARG = timestamptz, FUNCTION = uuidv7_le_timestamptz,
+    COMMUTATOR = >=, NEGATOR = >,
+    RESTRICT = scalarlesel, JOIN = scalarlejoinsel
+);
+
+CREATE OPERATOR > (
+    LEFTARG = uuidv7, RIGHTARG = timestamptz, FUNCTION = uuidv7_gt_timestamptz,
+    COMMUTATOR = <, NEGATOR = <=,
+    RESTRICT = scalargtsel, JOIN = scalargtjoinsel
+);
+
+CREATE OPERATOR >= (
+    LEFTARG = uuidv7, RIGHTARG = timestamptz, FUNCTION = uuidv7_ge_timestamptz,
+    COMMUTATOR = <=, NEGATOR = <,
+    RESTRICT = scalargesel, JOIN = scalargejoinsel
+);
+
+CREATE OPERATOR < (
+    LEFTARG = timestamptz, RIGHTARG = uuidv7, FUNCTION = timestamptz_lt_uuidv7,
+    COMMUTATOR = >, NEGATOR = >=,
+    RESTRICT = scalarltsel, JOIN = scalarltjoinsel
+);
+
+CREATE OPERATOR <= (
+    LEFTARG = timestamptz, RIGHTARG = uuidv7, FUNCTION = timestamptz_le_uuidv7,
+    COMMUTATOR = >=, NEGATOR = >,
+    RESTRICT = scalarlesel, JOIN = scalarlejoinsel
+);
+
+CREATE OPERATOR > (
+    LEFTARG = timestamptz, RIGHTARG = uuidv7, FUNCTION = timestamptz_gt_uuidv7,
+    COMMUTATOR = <, NEGATOR = <=,
+    RESTRICT = scalargtsel, JOIN = scalargtjoinsel
+);
+
+CREATE OPERATOR >= (
+    LEFTARG = timestamptz, RIGHTARG = uuidv7, FUNCTION = timestamptz_ge_uuidv7,
+    COMMUTATOR = <=, NEGATOR = <,
+    RESTRICT = scalargesel, JOIN = scalargejoinsel
+);
+
+CREATE FUNCTION uuidv7_contained_by_tstzrange(u uuidv7, r tstzrange) RETURNS bool
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    RETURN NOT isempty(r)
+        AND CASE
+            WHEN lower_inf(r) THEN u >= '00000000-0000-7000-8000-000000000000'::uuidv7
+            WHEN lower_inc(r) THEN u >= timestamptz_to_uuidv7_min_clamped(lower(r))
+            ELSE u > timestamptz_to_uuidv7_max_clamped(lower(r))
+        END
+        AND CASE
+            WHEN upper_inf(r) THEN u <= 'ffffffff-ffff-7fff-bfff-ffffffffffff'::uuidv7
+            WHEN upper_inc(r) THEN u <= timestamptz_to_uuidv7_max_clamped(upper(r))
+            ELSE u < timestamptz_to_uuidv7_min_clamped(upper(r))
+        END;
+
+CREATE FUNCTION tstzrange_contains_uuidv7(r tstzrange, u uuidv7) RETURNS bool
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    RETURN uuidv7_contained_by_tstzrange(u, r);
+
+CREATE OPERATOR <@ (
+    LEFTARG = uuidv7, RIGHTARG = tstzrange, FUNCTION = uuidv7_contained_by_tstzrange,
+    COMMUTATOR = @>,
+    RESTRICT = contsel, JOIN = contjoinsel
+);
+
+CREATE OPERATOR @> (
+    LEFTARG = tstzrange, RIGHTARG = uuidv7, FUNCTION = tstzrange_contains_uuidv7,
+    COMMUTATOR = <@,
+    RESTRICT = contsel, JOIN = contjoinsel
+);
+"#,
+    name = "uuidv7_timestamptz_operators",
+    requires = [
+        "type_uuidv7",
+        timestamptz_to_uuid_v7_min_clamped,
+        timestamptz_to_uuid_v7_max_clamped
+    ],
+);